@@ -4,6 +4,11 @@
 //! - floating point method using libm
 //! - fixed point using fixed_trigonometry
 //! - fixed point using pre-calculated table
+//! - fixed point using pre-calculated table, sin and cos with separate calls
+//!   compared with a single sincos call
+//! - fixed point using a binary angle quarter wave table, without comparisons
+//! - fixed point using CORDIC, without table
+//! - fixed point using minimax polynomials, without table
 //! The code will interact with usart for setting the calculation method
 //! and at each step increment the angle and update two PWM outputs with
 //! the sine and cosine of the angle. The frequecy of the output will be
@@ -32,6 +37,12 @@ use fixed::types::I2F14;
 use fixed::types::I4F12;
 mod sin_table;
 mod table_trig;
+mod bam_sin_table;
+mod bam_trig;
+mod electrical_angle;
+mod cordic;
+mod poly_trig;
+use electrical_angle::ElectricalAngle;
 
 #[entry]
 fn main() -> ! {
@@ -96,15 +107,16 @@ fn main() -> ! {
     let mut led1 = gpiob.pb14.into_push_pull_output(&mut gpiob.crh);
 
     let mut angle: I4F12;
-    let resolution = 12-7; // 2^-7
+    let mut bam_angle: ElectricalAngle;
     let mut sinval: I2F14;
+    let mut cosval: I2F14;
     let startangle: i32 = -3140;
     let endangle: i32 = 3140;
     let mut received;
     pwm.set_duty(Channel::C3, max/4);
 
     loop {
-        writeln!(tx, "Enter command 't' or 'l' ");
+        writeln!(tx, "Enter command 't' or 'l' or 's' or 'c' or 'b' or 'r' or 'p' ");
         received=block!(rx.read()).unwrap(); // receive commands from uart
         writeln!(tx, "Received {}, max duty {}",received, max);
         match received {
@@ -119,7 +131,7 @@ fn main() -> ! {
                     for i in startangle..endangle {
                         //led.set_high();
                         //led1.set_high();
-                        sinval = table_trig::sin_t(angle,&sin_table::SIN_TABLE, resolution); // about 400ns @ 64MHz
+                        sinval = table_trig::sin_t(angle,&sin_table::TABLE); // about 400ns @ 64MHz
                         //led1.set_low();
                         pwm.set_duty(Channel::C4, ((sinval.to_bits() >> 3)+32767/16) as u16);
                         //led1.set_high();
@@ -154,8 +166,89 @@ fn main() -> ! {
                 writeln!(tx, "end t");
                 //led.set_low();
             }
+            b's' => {
+                // sin and cos with two separate table calls, quadrant decoded twice
+                writeln!(tx, "Start s");
+                for j in 1..1000 {
+                    angle = I4F12::from_num(-3.14);
+                    led.set_high();
+                    for i in startangle..endangle {
+                        sinval = table_trig::sin_t(angle,&sin_table::TABLE);
+                        cosval = table_trig::cos_t(angle,&sin_table::TABLE);
+                        pwm.set_duty(Channel::C4, ((sinval.to_bits() >> 3)+32767/16) as u16);
+                        pwm.set_duty(Channel::C3, ((cosval.to_bits() >> 3)+32767/16) as u16);
+                        angle += I4F12::from_num(0.00101);
+                    }
+                    led.set_low();
+                }
+                writeln!(tx, "end s");
+            }
+            b'c' => {
+                // sin and cos with a single sincos table call, quadrant decoded once
+                writeln!(tx, "Start c");
+                for j in 1..1000 {
+                    angle = I4F12::from_num(-3.14);
+                    led.set_high();
+                    for i in startangle..endangle {
+                        (sinval, cosval) = table_trig::sincos_t(angle,&sin_table::TABLE);
+                        pwm.set_duty(Channel::C4, ((sinval.to_bits() >> 3)+32767/16) as u16);
+                        pwm.set_duty(Channel::C3, ((cosval.to_bits() >> 3)+32767/16) as u16);
+                        angle += I4F12::from_num(0.00101);
+                    }
+                    led.set_low();
+                }
+                writeln!(tx, "end c");
+            }
+            b'b' => {
+                // same as 't' with the binary angle table
+                writeln!(tx, "Start b");
+                let bam_step = ElectricalAngle::from_radians(I4F12::from_num(0.00101));
+                for j in 1..1000 {
+                    bam_angle = ElectricalAngle::from_radians(I4F12::from_num(-3.14));
+                    led.set_high();
+                    for i in startangle..endangle {
+                        sinval = bam_trig::sin_b(bam_angle,&bam_sin_table::BAM_TABLE);
+                        pwm.set_duty(Channel::C4, ((sinval.to_bits() >> 3)+32767/16) as u16);
+                        bam_angle += bam_step;
+                    }
+                    led.set_low();
+                }
+                writeln!(tx, "end b");
+            }
+            b'r' => {
+                // same as 't' with CORDIC
+                writeln!(tx, "Start r");
+                let iterations = 16; // about 2^-14 accuracy
+                for j in 1..1000 {
+                    angle = I4F12::from_num(-3.14);
+                    led.set_high();
+                    for i in startangle..endangle {
+                        sinval = cordic::sin_c(angle, iterations);
+                        pwm.set_duty(Channel::C4, ((sinval.to_bits() >> 3)+32767/16) as u16);
+                        angle += I4F12::from_num(0.00101);
+                    }
+                    led.set_low();
+                }
+                writeln!(tx, "end r");
+            }
+            b'p' => {
+                // same as 't' with the polynomials
+                writeln!(tx, "Start p");
+                let order = poly_trig::PolyOrder::Order3; // about 2^-9 accuracy
+                for j in 1..1000 {
+                    angle = I4F12::from_num(-3.14);
+                    led.set_high();
+                    for i in startangle..endangle {
+                        sinval = poly_trig::sin_p(angle, order);
+                        pwm.set_duty(Channel::C4, ((sinval.to_bits() >> 3)+32767/16) as u16);
+                        angle += I4F12::from_num(0.00101);
+                    }
+                    led.set_low();
+                }
+                writeln!(tx, "end p");
+            }
             _ => {
-                writeln!(tx, "Valid commands: 't' or 'l'");
+                writeln!(tx, "Valid commands: 't' or 'l' or 's' or 'c' or 'b' or 'r' or 'p'");
             }
         }
         writeln!(tx, "End Loop");
@@ -0,0 +1,205 @@
+use fixed::types::I2F14;
+use fixed::types::I4F12;
+use super::table_trig::{self, Trig};
+
+/*
+sin and cos with minimax polynomials, no table reads, only a few multiplications
+the angle is reduced to 0 to PI/2 like table_trig::sin_t and cos_t, then split again:
+0 to PI/4 uses the odd sin polynomial x*P(x^2),
+PI/4 to PI/2 uses the even cos polynomial Q(t^2) of t = PI/2 - x
+all the internal values are i32 fixed point with POLY_FRAC_NBITS fractional bits
+coefficients are minimax (Remez) on 0 to PI/4 for the absolute error, rounded to
+POLY_FRAC_NBITS and then moved by a few LSB to the least max error of this fixed point
+evaluation over every reduced I4F12 angle, the 2^-15 rounding of the products limits
+the error to about 3e-5 whatever the order
+*/
+const POLY_FRAC_NBITS: u32 = 15;
+
+// PI/4 and PI/2 with POLY_FRAC_NBITS fractional bits
+const FRAC_PI_4_Q15: i32 = 25736;
+const FRAC_PI_2_Q15: i32 = 51472;
+
+// sin(x) = x*(c0 + c1*x^2 + c2*x^4 + ...), cos(t) = c0 + c1*t^2 + c2*t^4 + ...
+// coefficients with POLY_FRAC_NBITS fractional bits, max error of the fixed point
+// evaluation on its half of the reduced angles, before the I2F14 rounding, in the comment
+const SIN_COEFFS_3: [i32; 2] = [32736, -5254]; // 1.7e-4
+const COS_COEFFS_2: [i32; 2] = [32705, -15559]; // 1.9e-3
+const SIN_COEFFS_5: [i32; 3] = [32768, -5461, 269]; // 2.8e-5
+const COS_COEFFS_4: [i32; 3] = [32768, -16376, 1326]; // 3.7e-5
+const SIN_COEFFS_7: [i32; 4] = [32768, -5461, 271, -4]; // 2.8e-5
+const COS_COEFFS_6: [i32; 4] = [32768, -16384, 1365, -44]; // 2.7e-5
+
+/*
+order of the polynomials, the degree of the sin polynomial, cos is one degree lower
+max error measured against f64, on the reduced angles 0 to PI/2 (I2F14 result) and
+on every I4F12 angle in -2PI to 2PI for sin and cos:
+Order3: 2.0e-3 reduced, sin 2.3e-3 cos 2.5e-3, half of the 2^-7 nearest minimax table
+Order5: 6.6e-5 reduced (about 1 LSB of I2F14), sin 4.9e-4 cos 7.7e-4, limited by the
+  truncated PI constants of the I4F12 angle reduction like table_trig sin_t_interp
+Order7: 5.9e-5 reduced, sin and cos as Order5, the extra term is lost in the 2^-15
+  rounding of the products, kept for comparison
+*/
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PolyOrder {
+    Order3,
+    Order5,
+    Order7,
+}
+
+impl PolyOrder {
+    /*
+    flash used by the coefficients of both polynomials
+    */
+    pub fn flash_bytes(self) -> usize {
+        let (sin_coeffs, cos_coeffs) = coefficients(self);
+        core::mem::size_of_val(sin_coeffs) + core::mem::size_of_val(cos_coeffs)
+    }
+}
+
+/*
+polynomial backend with the given order
+*/
+pub struct Poly {
+    pub order: PolyOrder,
+}
+
+impl Trig for Poly {
+    fn sin(&self, angle: I4F12) -> I2F14 {
+        sin_p(angle, self.order)
+    }
+
+    fn cos(&self, angle: I4F12) -> I2F14 {
+        cos_p(angle, self.order)
+    }
+
+    fn sincos(&self, angle: I4F12) -> (I2F14, I2F14) {
+        sincos_p(angle, self.order)
+    }
+}
+
+/*
+sin function approximation with polynomials,
+angle is fixed point I4F12 -8 to 7.999 res 0.00024 [rad]
+returns fixed point I2F14 -1 to +1
+like table_trig::sin_t angles beyond +-2PI return 0
+*/
+pub fn sin_p(angle: I4F12, order: PolyOrder) -> I2F14 {
+    match table_trig::sin_quadrant(angle) {
+        Some((angle_int, sign)) => apply_sign(quarter_wave(angle_int, order), sign),
+        None => I2F14::ZERO, // beyond +-2PI overflow management
+    }
+}
+
+/*
+cos function approximation with polynomials, see sin_p
+like table_trig::cos_t angles beyond +-2PI return 1
+*/
+pub fn cos_p(angle: I4F12, order: PolyOrder) -> I2F14 {
+    match table_trig::cos_quadrant(angle) {
+        Some((angle_int, sign)) => apply_sign(quarter_wave(angle_int, order), sign),
+        None => I2F14::ONE, // beyond +-2PI overflow management
+    }
+}
+
+/*
+sin and cos function approximation with polynomials in a single call, returns (sin, cos)
+same values of sin_p and cos_p, the quadrant of angle is decoded only once
+*/
+pub fn sincos_p(angle: I4F12, order: PolyOrder) -> (I2F14, I2F14) {
+    match table_trig::sincos_quadrant(angle) {
+        Some((sin_int, sin_sign, cos_int, cos_sign)) => (
+            apply_sign(quarter_wave(sin_int, order), sin_sign),
+            apply_sign(quarter_wave(cos_int, order), cos_sign),
+        ),
+        None => (I2F14::ZERO, I2F14::ONE), // beyond +-2PI overflow management
+    }
+}
+
+/*
+sin of the reduced angle 0 to PI/2, the same reduced angle used by the table lookup
+*/
+fn quarter_wave(angle_int: I4F12, order: PolyOrder) -> I2F14 {
+    let (sin_coeffs, cos_coeffs) = coefficients(order);
+    let x = (angle_int.to_bits() as i32) << (POLY_FRAC_NBITS - I4F12::FRAC_NBITS);
+    let value = if x <= FRAC_PI_4_Q15 {
+        mul(x, horner(sin_coeffs, mul(x, x)))
+    } else {
+        // cos is even, t slightly below 0 at the truncated PI/2 of I4F12 is fine
+        let t = FRAC_PI_2_Q15 - x;
+        horner(cos_coeffs, mul(t, t))
+    };
+    let shift = POLY_FRAC_NBITS - I2F14::FRAC_NBITS;
+    let bits = (value + (1 << (shift - 1))) >> shift;
+    I2F14::from_bits(bits.clamp(0, I2F14::ONE.to_bits() as i32) as i16)
+}
+
+fn coefficients(order: PolyOrder) -> (&'static [i32], &'static [i32]) {
+    match order {
+        PolyOrder::Order3 => (&SIN_COEFFS_3, &COS_COEFFS_2),
+        PolyOrder::Order5 => (&SIN_COEFFS_5, &COS_COEFFS_4),
+        PolyOrder::Order7 => (&SIN_COEFFS_7, &COS_COEFFS_6),
+    }
+}
+
+/*
+c0 + c1*x2 + c2*x2^2 + ... evaluated from the highest term
+*/
+fn horner(coeffs: &[i32], x2: i32) -> i32 {
+    coeffs.iter().rev().fold(0, |acc, c| c + mul(acc, x2))
+}
+
+/*
+product of two POLY_FRAC_NBITS values rounded to nearest, both below 2 in magnitude
+*/
+fn mul(a: i32, b: i32) -> i32 {
+    (a * b + (1 << (POLY_FRAC_NBITS - 1))) >> POLY_FRAC_NBITS
+}
+
+fn apply_sign(value: I2F14, sign: bool) -> I2F14 {
+    if sign {
+        value
+    } else {
+        -value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // documented max errors: reduced angle, sin and cos over -2PI to 2PI
+    const MAX_ERRORS: [(PolyOrder, f64, f64, f64); 3] = [
+        (PolyOrder::Order3, 2.0e-3, 2.3e-3, 2.5e-3),
+        (PolyOrder::Order5, 6.6e-5, 4.9e-4, 7.7e-4),
+        (PolyOrder::Order7, 5.9e-5, 4.9e-4, 7.7e-4),
+    ];
+
+    #[test]
+    fn reduced_angle_error() {
+        // every reduced angle of table_trig, 0 to PI/2 plus the truncation of the constants
+        let (frac_pi_2, pi, three_frac_pi_2) = (I4F12::FRAC_PI_2, I4F12::PI, I4F12::FRAC_PI_2 * 3);
+        let max_reduced = (pi + pi - three_frac_pi_2).max(frac_pi_2).to_bits();
+        for (order, max_error, _, _) in MAX_ERRORS {
+            for bits in 0..=max_reduced {
+                let angle = I4F12::from_bits(bits);
+                let error = (quarter_wave(angle, order).to_num::<f64>() - angle.to_num::<f64>().sin()).abs();
+                assert!(error <= max_error, "{:?} at {}: error {}", order, angle, error);
+            }
+        }
+    }
+
+    #[test]
+    fn sin_cos_error() {
+        let two_pi = (2 * I4F12::PI).to_bits();
+        for (order, _, sin_error, cos_error) in MAX_ERRORS {
+            for bits in -two_pi + 1..two_pi {
+                let angle = I4F12::from_bits(bits);
+                let angle_f: f64 = angle.to_num();
+                let (sin, cos) = sincos_p(angle, order);
+                assert_eq!((sin, cos), (sin_p(angle, order), cos_p(angle, order)), "{:?} sincos {}", order, angle);
+                assert!((sin.to_num::<f64>() - angle_f.sin()).abs() <= sin_error, "{:?} sin {}: {}", order, angle, sin);
+                assert!((cos.to_num::<f64>() - angle_f.cos()).abs() <= cos_error, "{:?} cos {}: {}", order, angle, cos);
+            }
+        }
+    }
+}
@@ -1,204 +1,149 @@
-pub const SIN_TABLE:[i16;202]=[
-0, //I2F14::from_num(0)
-128, //I2F14::from_num(0.0078)
-256, //I2F14::from_num(0.0156)
-384, //I2F14::from_num(0.02344)
-512, //I2F14::from_num(0.03125)
-640, //I2F14::from_num(0.03906)
-768, //I2F14::from_num(0.0469)
-896, //I2F14::from_num(0.0547)
-1023, //I2F14::from_num(0.06244)
-1151, //I2F14::from_num(0.07025)
-1279, //I2F14::from_num(0.07806)
-1406, //I2F14::from_num(0.0858)
-1534, //I2F14::from_num(0.0936)
-1661, //I2F14::from_num(0.1014)
-1788, //I2F14::from_num(0.10913)
-1916, //I2F14::from_num(0.11694)
-2043, //I2F14::from_num(0.1247)
-2170, //I2F14::from_num(0.13245)
-2296, //I2F14::from_num(0.14014)
-2423, //I2F14::from_num(0.1479)
-2550, //I2F14::from_num(0.15564)
-2676, //I2F14::from_num(0.1633)
-2802, //I2F14::from_num(0.171)
-2928, //I2F14::from_num(0.1787)
-3054, //I2F14::from_num(0.1864)
-3180, //I2F14::from_num(0.1941)
-3305, //I2F14::from_num(0.2017)
-3430, //I2F14::from_num(0.20935)
-3555, //I2F14::from_num(0.217)
-3680, //I2F14::from_num(0.2246)
-3805, //I2F14::from_num(0.23224)
-3929, //I2F14::from_num(0.2398)
-4053, //I2F14::from_num(0.2474)
-4177, //I2F14::from_num(0.25494)
-4301, //I2F14::from_num(0.2625)
-4424, //I2F14::from_num(0.27)
-4547, //I2F14::from_num(0.2775)
-4670, //I2F14::from_num(0.28503)
-4793, //I2F14::from_num(0.29254)
-4915, //I2F14::from_num(0.3)
-5037, //I2F14::from_num(0.30743)
-5159, //I2F14::from_num(0.3149)
-5280, //I2F14::from_num(0.32227)
-5401, //I2F14::from_num(0.32965)
-5522, //I2F14::from_num(0.33704)
-5642, //I2F14::from_num(0.34436)
-5762, //I2F14::from_num(0.3517)
-5882, //I2F14::from_num(0.359)
-6001, //I2F14::from_num(0.3663)
-6120, //I2F14::from_num(0.37354)
-6238, //I2F14::from_num(0.38074)
-6357, //I2F14::from_num(0.388)
-6474, //I2F14::from_num(0.39514)
-6592, //I2F14::from_num(0.40234)
-6709, //I2F14::from_num(0.4095)
-6825, //I2F14::from_num(0.41656)
-6942, //I2F14::from_num(0.4237)
-7057, //I2F14::from_num(0.4307)
-7173, //I2F14::from_num(0.4378)
-7287, //I2F14::from_num(0.44476)
-7402, //I2F14::from_num(0.4518)
-7516, //I2F14::from_num(0.45874)
-7629, //I2F14::from_num(0.46564)
-7742, //I2F14::from_num(0.47253)
-7855, //I2F14::from_num(0.47943)
-7967, //I2F14::from_num(0.48627)
-8079, //I2F14::from_num(0.4931)
-8190, //I2F14::from_num(0.4999)
-8300, //I2F14::from_num(0.5066)
-8410, //I2F14::from_num(0.5133)
-8520, //I2F14::from_num(0.52)
-8629, //I2F14::from_num(0.5267)
-8738, //I2F14::from_num(0.5333)
-8846, //I2F14::from_num(0.5399)
-8953, //I2F14::from_num(0.54645)
-9060, //I2F14::from_num(0.553)
-9166, //I2F14::from_num(0.55945)
-9272, //I2F14::from_num(0.5659)
-9377, //I2F14::from_num(0.5723)
-9482, //I2F14::from_num(0.57874)
-9586, //I2F14::from_num(0.5851)
-9690, //I2F14::from_num(0.59143)
-9793, //I2F14::from_num(0.5977)
-9895, //I2F14::from_num(0.60394)
-9997, //I2F14::from_num(0.61017)
-10098, //I2F14::from_num(0.61633)
-10198, //I2F14::from_num(0.62244)
-10298, //I2F14::from_num(0.62854)
-10397, //I2F14::from_num(0.6346)
-10496, //I2F14::from_num(0.6406)
-10594, //I2F14::from_num(0.6466)
-10691, //I2F14::from_num(0.6525)
-10788, //I2F14::from_num(0.65845)
-10884, //I2F14::from_num(0.6643)
-10979, //I2F14::from_num(0.6701)
-11074, //I2F14::from_num(0.6759)
-11168, //I2F14::from_num(0.68164)
-11261, //I2F14::from_num(0.6873)
-11354, //I2F14::from_num(0.693)
-11446, //I2F14::from_num(0.6986)
-11537, //I2F14::from_num(0.70416)
-11628, //I2F14::from_num(0.7097)
-11717, //I2F14::from_num(0.71515)
-11807, //I2F14::from_num(0.72064)
-11895, //I2F14::from_num(0.726)
-11983, //I2F14::from_num(0.7314)
-12070, //I2F14::from_num(0.7367)
-12156, //I2F14::from_num(0.74194)
-12241, //I2F14::from_num(0.74713)
-12326, //I2F14::from_num(0.7523)
-12410, //I2F14::from_num(0.75745)
-12493, //I2F14::from_num(0.7625)
-12575, //I2F14::from_num(0.7675)
-12657, //I2F14::from_num(0.7725)
-12738, //I2F14::from_num(0.77747)
-12818, //I2F14::from_num(0.78235)
-12897, //I2F14::from_num(0.7872)
-12976, //I2F14::from_num(0.792)
-13054, //I2F14::from_num(0.79675)
-13131, //I2F14::from_num(0.80145)
-13207, //I2F14::from_num(0.8061)
-13282, //I2F14::from_num(0.81067)
-13357, //I2F14::from_num(0.81525)
-13430, //I2F14::from_num(0.8197)
-13503, //I2F14::from_num(0.82416)
-13575, //I2F14::from_num(0.82855)
-13647, //I2F14::from_num(0.83295)
-13717, //I2F14::from_num(0.8372)
-13787, //I2F14::from_num(0.8415)
-13855, //I2F14::from_num(0.84564)
-13923, //I2F14::from_num(0.8498)
-13990, //I2F14::from_num(0.8539)
-14057, //I2F14::from_num(0.858)
-14122, //I2F14::from_num(0.86194)
-14186, //I2F14::from_num(0.86584)
-14250, //I2F14::from_num(0.86975)
-14313, //I2F14::from_num(0.8736)
-14375, //I2F14::from_num(0.8774)
-14435, //I2F14::from_num(0.88104)
-14496, //I2F14::from_num(0.88477)
-14555, //I2F14::from_num(0.88837)
-14613, //I2F14::from_num(0.8919)
-14671, //I2F14::from_num(0.89545)
-14727, //I2F14::from_num(0.89886)
-14783, //I2F14::from_num(0.9023)
-14837, //I2F14::from_num(0.9056)
-14891, //I2F14::from_num(0.9089)
-14944, //I2F14::from_num(0.9121)
-14996, //I2F14::from_num(0.9153)
-15047, //I2F14::from_num(0.9184)
-15098, //I2F14::from_num(0.9215)
-15147, //I2F14::from_num(0.9245)
-15195, //I2F14::from_num(0.9274)
-15243, //I2F14::from_num(0.93036)
-15289, //I2F14::from_num(0.93317)
-15335, //I2F14::from_num(0.936)
-15379, //I2F14::from_num(0.93866)
-15423, //I2F14::from_num(0.94135)
-15466, //I2F14::from_num(0.944)
-15507, //I2F14::from_num(0.9465)
-15548, //I2F14::from_num(0.949)
-15588, //I2F14::from_num(0.9514)
-15627, //I2F14::from_num(0.9538)
-15665, //I2F14::from_num(0.9561)
-15702, //I2F14::from_num(0.9584)
-15738, //I2F14::from_num(0.9606)
-15773, //I2F14::from_num(0.9627)
-15807, //I2F14::from_num(0.9648)
-15840, //I2F14::from_num(0.9668)
-15873, //I2F14::from_num(0.9688)
-15904, //I2F14::from_num(0.9707)
-15934, //I2F14::from_num(0.97253)
-15964, //I2F14::from_num(0.97437)
-15992, //I2F14::from_num(0.9761)
-16019, //I2F14::from_num(0.9777)
-16046, //I2F14::from_num(0.9794)
-16071, //I2F14::from_num(0.9809)
-16095, //I2F14::from_num(0.98236)
-16119, //I2F14::from_num(0.9838)
-16141, //I2F14::from_num(0.98517)
-16163, //I2F14::from_num(0.9865)
-16183, //I2F14::from_num(0.98773)
-16203, //I2F14::from_num(0.98895)
-16221, //I2F14::from_num(0.99005)
-16239, //I2F14::from_num(0.99115)
-16255, //I2F14::from_num(0.9921)
-16271, //I2F14::from_num(0.9931)
-16285, //I2F14::from_num(0.99396)
-16299, //I2F14::from_num(0.9948)
-16311, //I2F14::from_num(0.99554)
-16323, //I2F14::from_num(0.9963)
-16333, //I2F14::from_num(0.9969)
-16343, //I2F14::from_num(0.9975)
-16352, //I2F14::from_num(0.99805)
-16359, //I2F14::from_num(0.9985)
-16366, //I2F14::from_num(0.9989)
-16371, //I2F14::from_num(0.9992)
-16376, //I2F14::from_num(0.9995)
-16379, //I2F14::from_num(0.9997)
-16382, //I2F14::from_num(0.9999)
-16383, //I2F14::from_num(0.99994)
-16384, //I2F14::from_num(1)
-];
+use fixed::types::I2F14;
+use fixed::types::I4F12;
+use super::table_trig::{SinTable, MAX_REDUCED_ANGLE};
+
+/*
+sin table created at compile time, used by table_trig sin_t and cos_t
+the table goes from 0 to the largest angle the quadrant decode of table_trig gives,
+PI/2 of the angle type I4F12 plus the truncation of its constants, end point included,
+with one sample every 2^DIVISION_SHIFT angle steps, values are the raw bits of the
+output type I2F14
+to change the table resolution change DIVISION_SHIFT, table size follows:
+DIVISION_SHIFT=12-7 -> resolution 2^-7 rad, 202 points
+DIVISION_SHIFT=12-6 -> resolution 2^-6 rad, 101 points
+DIVISION_SHIFT=0 -> full resolution 2^-12 rad, 6436 points
+TABLE_MODE chooses how the points are computed, see TableMode
+TABLE ties SIN_TABLE to DIVISION_SHIFT, its output type and end angle and is the one
+to pass to the trig functions, it is checked at compile time: a table too short for its
+DIVISION_SHIFT or not of I2F14 samples does not build
+*/
+pub const DIVISION_SHIFT: u16 = 12-7;
+pub const TABLE_MODE: TableMode = TableMode::LeftEdge;
+pub const SIN_TABLE_LEN: usize = SinTable::required_len(DIVISION_SHIFT);
+pub const SIN_TABLE: [i16; SIN_TABLE_LEN] = create_table(I4F12::FRAC_NBITS, I2F14::FRAC_NBITS, DIVISION_SHIFT, TABLE_MODE);
+pub const TABLE: SinTable<'static> = SinTable::new(&SIN_TABLE, DIVISION_SHIFT, I2F14::FRAC_NBITS, MAX_REDUCED_ANGLE);
+
+/*
+how the table points are computed, the angles a point is used for are its bucket:
+for sin_t (nearest) the angles from the point to the next one excluded, for
+sin_t_interp the segments on both sides of the point
+LeftEdge: sin of the point angle, the error of sin_t is one-sided
+NearestMinimax: middle of the sin values of the bucket, halves the max error of sin_t
+NearestRms: mean of the sin values of the bucket, least RMS error of sin_t
+InterpolatedMinimax: sin of the point angle raised by 2/(1+cos(h/2)), h the step [rad],
+  the chord error then goes from -e to +e instead of 0 to 2e, for sin_t_interp
+InterpolatedRms: sin of the point angle raised to the least RMS error of the chord,
+  for sin_t_interp
+*/
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TableMode {
+    LeftEdge,
+    NearestMinimax,
+    NearestRms,
+    InterpolatedMinimax,
+    InterpolatedRms,
+}
+
+/*
+create a sin table, same algorithm of Sin_Table_Create:
+the point i is computed from the sin of angle i*2^division_shift in angle_frac_nbits
+fixed point as set by mode, rounded to nearest in output_frac_nbits fixed point,
+limited to the i16 maximum
+the length N of the array sets the end angle, see SinTable::required_len
+*/
+pub const fn create_table<const N: usize>(angle_frac_nbits: u32, output_frac_nbits: u32, division_shift: u16, mode: TableMode) -> [i16; N] {
+    let mut table = [0; N];
+    fill_table(&mut table, angle_frac_nbits, output_frac_nbits, division_shift, mode);
+    table
+}
+
+/*
+same as create_table on a slice, for tables created at run time, e.g.
+let mut data = vec![0; SinTable::required_len(division_shift)];
+*/
+pub const fn fill_table(table: &mut [i16], angle_frac_nbits: u32, output_frac_nbits: u32, division_shift: u16, mode: TableMode) {
+    let resolution = 1.0 / (1u64 << angle_frac_nbits) as f64;
+    let step = (1u64 << division_shift) as f64 * resolution;
+    // largest reduced angle of the table_trig quadrant decode, 2PI - 3/2PI truncated
+    let max_reduced = 2 * (core::f64::consts::PI / resolution) as u64 - 3 * (core::f64::consts::FRAC_PI_2 / resolution) as u64;
+    let mut i = 0;
+    while i < table.len() {
+        let first = (i as u64) << division_shift;
+        let sin_first = sin_f64(first as f64 * resolution);
+        let value = match mode {
+            TableMode::LeftEdge => sin_first,
+            TableMode::NearestMinimax | TableMode::NearestRms => {
+                let mut last = first + (1u64 << division_shift) - 1;
+                if last > max_reduced {
+                    last = max_reduced;
+                }
+                // min and max, or sum, of the sin values of the bucket, sin has its
+                // maximum at PI/2 that can be inside the last buckets
+                let (mut min, mut max, mut sum) = (sin_first, sin_first, 0.0);
+                let mut angle = first;
+                while angle <= last {
+                    let value = sin_f64(angle as f64 * resolution);
+                    if value < min {
+                        min = value;
+                    }
+                    if value > max {
+                        max = value;
+                    }
+                    sum += value;
+                    angle += 1;
+                }
+                if let TableMode::NearestMinimax = mode {
+                    (min + max) / 2.0
+                } else {
+                    sum / (last - first + 1) as f64
+                }
+            }
+            TableMode::InterpolatedMinimax => {
+                // cos(h/2) = sin(PI/2 - h/2)
+                sin_first * 2.0 / (1.0 + sin_f64(core::f64::consts::FRAC_PI_2 - step / 2.0))
+            }
+            TableMode::InterpolatedRms => {
+                // the chord is about sin*(1-q), q=t*(h-t)/2 along the step, mean q is h^2/12
+                // and mean q^2 is h^4/120, the least squares gain is (1-E[q])/E[(1-q)^2]
+                let h2 = step * step;
+                sin_first * (1.0 - h2 / 12.0) / (1.0 - h2 / 6.0 + h2 * h2 / 120.0)
+            }
+        };
+        table[i] = to_fixed_bits(value, output_frac_nbits);
+        i += 1;
+    }
+}
+
+/*
+sin for 0 to PI/2 with Taylor series, usable in const fn
+the terms are added until they are smaller than the f64 resolution
+*/
+pub const fn sin_f64(angle: f64) -> f64 {
+    let mut term = angle;
+    let mut sum = angle;
+    let mut n = 1.0;
+    while term > 1e-20 || term < -1e-20 {
+        term = -term * angle * angle / ((n + 1.0) * (n + 2.0));
+        sum += term;
+        n += 2.0;
+    }
+    sum
+}
+
+/*
+converts a positive value to the raw bits of a fixed point with frac_nbits fractional
+bits, rounded to nearest with ties to even like fixed from_num, limited to i16 maximum
+*/
+pub const fn to_fixed_bits(value: f64, frac_nbits: u32) -> i16 {
+    let scaled = value * (1u64 << frac_nbits) as f64;
+    let mut bits = scaled as i64;
+    let rest = scaled - bits as f64;
+    if rest > 0.5 || (rest == 0.5 && bits % 2 == 1) {
+        bits += 1;
+    }
+    if bits > i16::MAX as i64 {
+        i16::MAX
+    } else {
+        bits as i16
+    }
+}
@@ -0,0 +1,119 @@
+use std::io::prelude::*;
+use fixed::types::I2F14;
+use fixed::types::I4F12;
+
+/*
+histogram of the absolute error in I2F14 steps (2^-14), the first bin is below half a
+step (exact after rounding), then one bin per power of two: [0.5, 1), [1, 2) ... [128, 256),
+the last bin is 256 steps and more
+*/
+pub const HISTOGRAM_BINS: usize = 11;
+
+/*
+error statistics of one function against f64 over every I4F12 angle in -2PI to 2PI,
+beyond +-2PI the functions return fixed values and are not included
+error is reference - approximation
+*/
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ErrorStats {
+    pub count: usize,
+    pub max_abs: f64,
+    pub max_abs_angle: f64,
+    pub rms: f64,
+    pub mean: f64,
+    pub histogram: [usize; HISTOGRAM_BINS],
+}
+
+/*
+computes the error statistics of approximation against reference
+*/
+pub fn error_stats(approximation: impl Fn(I4F12) -> I2F14, reference: fn(f64) -> f64) -> ErrorStats {
+    let mut stats = ErrorStats::default();
+    let mut sum = 0.0;
+    let mut sum_squares = 0.0;
+    for bits in (-2 * I4F12::PI).to_bits() + 1..(2 * I4F12::PI).to_bits() {
+        let angle = I4F12::from_bits(bits);
+        let angle_f: f64 = angle.to_num();
+        let error = reference(angle_f) - f64::from(approximation(angle));
+        if error.abs() > stats.max_abs {
+            stats.max_abs = error.abs();
+            stats.max_abs_angle = angle_f;
+        }
+        sum += error;
+        sum_squares += error * error;
+        stats.count += 1;
+        stats.histogram[histogram_bin(error)] += 1;
+    }
+    stats.mean = sum / stats.count as f64;
+    stats.rms = (sum_squares / stats.count as f64).sqrt();
+    stats
+}
+
+fn histogram_bin(error: f64) -> usize {
+    let steps = error.abs() / I2F14::DELTA.to_num::<f64>();
+    if steps < 0.5 {
+        0
+    } else {
+        // [0.5, 1) -> 1, [1, 2) -> 2 ...
+        ((steps.log2().floor() as i32 + 2).max(1) as usize).min(HISTOGRAM_BINS - 1)
+    }
+}
+
+/*
+lower edge of the histogram bin in I2F14 steps
+*/
+pub fn histogram_edge(bin: usize) -> f64 {
+    if bin == 0 {
+        0.0
+    } else {
+        2f64.powi(bin as i32 - 2)
+    }
+}
+
+/*
+console report, one line per function
+*/
+pub fn print_report(name: &str, function: &str, stats: &ErrorStats) {
+    println!("{} {}: max error {:.6} at {:.4} rad, rms {:.6}, mean {:+.6}", name, function, stats.max_abs, stats.max_abs_angle, stats.rms, stats.mean);
+    let histogram: Vec<String> = stats.histogram.iter().enumerate()
+    .filter(|(_, count)| **count > 0)
+    .map(|(bin, count)| format!(">={}:{}", histogram_edge(bin), count))
+    .collect();
+    println!("    error histogram [2^-14 steps] {}", histogram.join(" "));
+}
+
+/*
+CSV with one line per method and function, the histogram bins are columns named by
+their lower edge in I2F14 steps
+*/
+pub fn write_csv(fileh: &mut impl Write, results: &[(String, &str, ErrorStats)]) -> std::io::Result<()> {
+    write!(fileh,"method,function,count,max_abs,max_abs_angle,rms,mean")?;
+    for bin in 0..HISTOGRAM_BINS {
+        write!(fileh,",hist_{}",histogram_edge(bin))?;
+    }
+    writeln!(fileh)?;
+    for (name, function, stats) in results {
+        write!(fileh,"{},{},{},{},{},{},{}",name,function,stats.count,stats.max_abs,stats.max_abs_angle,stats.rms,stats.mean)?;
+        for count in stats.histogram.iter() {
+            write!(fileh,",{}",count)?;
+        }
+        writeln!(fileh)?;
+    }
+    Ok(())
+}
+
+/*
+JSON array with one object per method and function
+*/
+pub fn write_json(fileh: &mut impl Write, results: &[(String, &str, ErrorStats)]) -> std::io::Result<()> {
+    writeln!(fileh,"[")?;
+    for (i, (name, function, stats)) in results.iter().enumerate() {
+        let edges: Vec<String> = (0..HISTOGRAM_BINS).map(|bin| histogram_edge(bin).to_string()).collect();
+        let counts: Vec<String> = stats.histogram.iter().map(|count| count.to_string()).collect();
+        write!(fileh,"  {{\"method\": \"{}\", \"function\": \"{}\", \"count\": {}, \"max_abs\": {}, \"max_abs_angle\": {}, \"rms\": {}, \"mean\": {}, \"histogram_edges\": [{}], \"histogram\": [{}]}}",
+        name,function,stats.count,stats.max_abs,stats.max_abs_angle,stats.rms,stats.mean,edges.join(", "),counts.join(", "))?;
+        writeln!(fileh,"{}",if i + 1 < results.len() { "," } else { "" })?;
+    }
+    writeln!(fileh,"]")?;
+    Ok(())
+}
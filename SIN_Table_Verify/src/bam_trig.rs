@@ -0,0 +1,190 @@
+use fixed::types::I2F14;
+use fixed::types::I4F12;
+use super::electrical_angle::ElectricalAngle;
+use super::table_trig::Trig;
+
+/*
+quarter wave table of the binary angle functions: the samples of 0 to PI/2 included,
+one every 2^division_shift steps of the 16 bit angle, raw bits of I2F14
+BamTable::new checks once that division_shift is 14 or less and that the length is
+2^(14-division_shift)+1, so the index sin_b computes is in range and the lookup cannot
+panic, in a const (e.g. bam_sin_table::BAM_TABLE) the check is done at compile time
+*/
+#[derive(Clone, Copy, Debug)]
+pub struct BamTable<'a> {
+    data: &'a [i16],
+    division_shift: u16,
+}
+
+impl<'a> BamTable<'a> {
+    /*
+    panics if division_shift is above 14 or if data is not required_len(division_shift) long
+    */
+    pub const fn new(data: &'a [i16], division_shift: u16) -> Self {
+        assert!(division_shift <= 14, "division_shift must be 14 or less");
+        assert!(data.len() == Self::required_len(division_shift), "bam table length does not match its division_shift");
+        BamTable { data, division_shift }
+    }
+
+    /*
+    number of samples for division_shift, PI/2 included
+    */
+    pub const fn required_len(division_shift: u16) -> usize {
+        (1 << (14 - division_shift)) + 1
+    }
+
+    pub const fn data(&self) -> &'a [i16] {
+        self.data
+    }
+
+    pub const fn division_shift(&self) -> u16 {
+        self.division_shift
+    }
+}
+
+/*
+sin function approximation, using a quarter wave table fixed point 0 to 1,
+angle is a 16 bit binary angle, 65536 is 2PI, res 0.000096 [rad]
+returns fixed point I2F14 -1 to +1
+table is a BamTable, the samples with their division_shift,
+the table covers 0 to PI/2 included with 2^(14-division_shift)+1 points
+if division_shift=0 the table will have 2^14+1 points
+if division_shift=6 the table will have 2^8+1 points
+...
+there are no comparisons on the angle:
+the top 2 bits of the angle are the quadrant, the next 14-division_shift bits are the
+table index, the second and fourth quadrant read the table backwards (mirror) and
+the third and fourth quadrant are negated, both done with masks
+*/
+pub fn sin_b(angle: ElectricalAngle, table: &BamTable) -> I2F14 {
+    let division_shift = table.division_shift;
+    let bits = angle.to_bits() as i32;
+    let steps = 1 << (14 - division_shift); // table points per quarter wave, plus the last one
+    let index = (bits >> division_shift) & (steps - 1);
+    // mirror is -1 in quadrants 2 and 4, 0 otherwise: index -> steps - index
+    let mirror = -((bits >> 14) & 1);
+    let index = (index ^ mirror) - mirror + (mirror & steps);
+    // negate is -1 in quadrants 3 and 4, 0 otherwise: value -> -value
+    let negate = -((bits >> 15) & 1);
+    let value = table.data[index as usize] as i32;
+    I2F14::from_bits(((value ^ negate) - negate) as i16)
+}
+
+/*
+cos function approximation, same table and angle of sin_b
+cos(angle) = sin(angle + PI/2), PI/2 is 16384 and the sum wraps
+*/
+pub fn cos_b(angle: ElectricalAngle, table: &BamTable) -> I2F14 {
+    sin_b(angle + ElectricalAngle::from_bits(1 << 14), table)
+}
+
+/*
+sin and cos function approximation in a single call, returns (sin, cos)
+*/
+pub fn sincos_b(angle: ElectricalAngle, table: &BamTable) -> (I2F14, I2F14) {
+    (sin_b(angle, table), cos_b(angle, table))
+}
+
+/*
+binary angle backend for the Trig interface, the I4F12 angle is converted with
+ElectricalAngle::from_radians, so angles beyond +-2PI wrap instead of being limited
+*/
+pub struct BamTrig<'a> {
+    pub table: BamTable<'a>,
+}
+
+impl Trig for BamTrig<'_> {
+    fn sin(&self, angle: I4F12) -> I2F14 {
+        sin_b(ElectricalAngle::from_radians(angle), &self.table)
+    }
+
+    fn cos(&self, angle: I4F12) -> I2F14 {
+        cos_b(ElectricalAngle::from_radians(angle), &self.table)
+    }
+
+    fn sincos(&self, angle: I4F12) -> (I2F14, I2F14) {
+        sincos_b(ElectricalAngle::from_radians(angle), &self.table)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::bam_sin_table::{create_bam_table, BAM_DIVISION_SHIFT, BAM_TABLE};
+    use core::f64::consts::PI;
+
+    fn radians(bits: u16) -> f64 {
+        bits as f64 * 2.0 * PI / 65536.0
+    }
+
+    fn lsb(value: I2F14) -> f64 {
+        value.to_num::<f64>() * 16384.0
+    }
+
+    /*
+    every binary angle against f64: at the table samples the value is the rounded sin
+    of that angle, within the 1 step of the table rounding, so the quadrant masks pick
+    the right sample and sign, in between it is off by at most one table step of angle
+    */
+    fn check_every_angle(table: &BamTable) {
+        let division_shift = table.division_shift();
+        let step = radians(1 << division_shift);
+        for bits in 0..=u16::MAX {
+            let angle = ElectricalAngle::from_bits(bits);
+            let (sin, cos) = (sin_b(angle, table), cos_b(angle, table));
+            assert_eq!(sincos_b(angle, table), (sin, cos), "sincos {}", bits);
+            let (sin_f, cos_f) = (radians(bits).sin(), radians(bits).cos());
+            if bits % (1 << division_shift) == 0 {
+                assert!((lsb(sin) - sin_f * 16384.0).abs() <= 1.0, "sin {} shift {}: {}", bits, division_shift, sin);
+                assert!((lsb(cos) - cos_f * 16384.0).abs() <= 1.0, "cos {} shift {}: {}", bits, division_shift, cos);
+            }
+            assert!((sin.to_num::<f64>() - sin_f).abs() <= step + 1.0 / 16384.0, "sin {} shift {}: {}", bits, division_shift, sin);
+            assert!((cos.to_num::<f64>() - cos_f).abs() <= step + 1.0 / 16384.0, "cos {} shift {}: {}", bits, division_shift, cos);
+        }
+    }
+
+    #[test]
+    fn shipped_table() {
+        check_every_angle(&BAM_TABLE);
+    }
+
+    #[test]
+    fn full_and_smallest_tables() {
+        let full: [i16; (1 << 14) + 1] = create_bam_table(I2F14::FRAC_NBITS);
+        check_every_angle(&BamTable::new(&full, 0));
+        let coarse: [i16; (1 << 4) + 1] = create_bam_table(I2F14::FRAC_NBITS);
+        check_every_angle(&BamTable::new(&coarse, 10));
+        // only 0 and PI/2, the quadrant masks alone give 0 1 0 -1
+        let data: [i16; 2] = create_bam_table(I2F14::FRAC_NBITS);
+        let smallest = BamTable::new(&data, 14);
+        for (bits, sin, cos) in [(0, 0, 1), (16384, 1, 0), (32768, 0, -1), (49152, -1, 0)] {
+            let angle = ElectricalAngle::from_bits(bits);
+            assert_eq!(sincos_b(angle, &smallest), (I2F14::from_num(sin), I2F14::from_num(cos)), "{}", bits);
+        }
+    }
+
+    #[test]
+    fn symmetries() {
+        // at the samples sin is odd, cos even and cos is sin shifted by PI/2, exactly
+        let step = 1 << BAM_DIVISION_SHIFT;
+        for bits in (0..=u16::MAX).step_by(step) {
+            let angle = ElectricalAngle::from_bits(bits);
+            let minus = ElectricalAngle::ZERO - angle;
+            assert_eq!(sin_b(minus, &BAM_TABLE), -sin_b(angle, &BAM_TABLE), "{}", bits);
+            assert_eq!(cos_b(minus, &BAM_TABLE), cos_b(angle, &BAM_TABLE), "{}", bits);
+        }
+    }
+    #[test]
+    #[should_panic(expected = "length does not match its division_shift")]
+    fn short_table_rejected() {
+        let data = vec![0; BamTable::required_len(BAM_DIVISION_SHIFT) - 1];
+        BamTable::new(&data, BAM_DIVISION_SHIFT);
+    }
+
+    #[test]
+    #[should_panic(expected = "division_shift must be 14 or less")]
+    fn large_shift_rejected() {
+        // 2 points already cover the quarter wave at 14, 15 would underflow the index width
+        BamTable::new(&[0, 16384], 15);
+    }
+}
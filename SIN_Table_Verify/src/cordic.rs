@@ -0,0 +1,327 @@
+use fixed::traits::FixedSigned;
+use fixed::types::I2F14;
+use fixed::types::I4F12;
+use super::table_trig::Trig;
+
+/*
+CORDIC sin, cos, atan2 and magnitude, only shifts, adds and two small constant tables
+all the internal values are i32 fixed point with CORDIC_FRAC_NBITS fractional bits,
+range -8 to 7.999, enough for the PI angles and for the vectoring mode gain of 1.65
+each iteration adds about one bit of accuracy, 16 iterations are enough for the
+2^-14 resolution of I2F14, the maximum is CORDIC_MAX_ITERATIONS
+flash used by the two tables is CORDIC_FLASH_BYTES
+*/
+const CORDIC_FRAC_NBITS: u32 = 28;
+pub const CORDIC_MAX_ITERATIONS: u8 = 24;
+pub const CORDIC_FLASH_BYTES: usize = 2 * 4 * CORDIC_MAX_ITERATIONS as usize;
+
+// PI and PI/2 with CORDIC_FRAC_NBITS fractional bits
+const PI_Q28: i32 = 843314857;
+const FRAC_PI_2_Q28: i32 = 421657428;
+
+// shift of the 16 bit raw inputs of the vectoring mode, CORDIC_FRAC_NBITS - 14
+const POLAR_SHIFT: u32 = CORDIC_FRAC_NBITS - 14;
+
+// atan(2^-i) with CORDIC_FRAC_NBITS fractional bits
+const ATAN_TABLE: [i32; CORDIC_MAX_ITERATIONS as usize] = [
+    210828714, 124459457, 65760959, 33381290, 16755422, 8385879, 4193963, 2097109,
+    1048571, 524287, 262144, 131072, 65536, 32768, 16384, 8192,
+    4096, 2048, 1024, 512, 256, 128, 64, 32,
+];
+
+// gain compensation after i+1 iterations, product of 1/sqrt(1+2^-2k) for k=0..i,
+// with CORDIC_FRAC_NBITS fractional bits
+const GAIN_TABLE: [i32; CORDIC_MAX_ITERATIONS as usize] = [
+    189812531, 169773489, 164704477, 163432609, 163114337, 163034749, 163014851, 163009877,
+    163008633, 163008322, 163008244, 163008225, 163008220, 163008219, 163008219, 163008219,
+    163008219, 163008219, 163008219, 163008219, 163008219, 163008219, 163008219, 163008219,
+];
+
+/*
+CORDIC backend with the given number of iterations, 1 to CORDIC_MAX_ITERATIONS
+*/
+pub struct Cordic {
+    pub iterations: u8,
+}
+
+impl Trig for Cordic {
+    fn sin(&self, angle: I4F12) -> I2F14 {
+        sin_c(angle, self.iterations)
+    }
+
+    fn cos(&self, angle: I4F12) -> I2F14 {
+        cos_c(angle, self.iterations)
+    }
+
+    fn sincos(&self, angle: I4F12) -> (I2F14, I2F14) {
+        sincos_c(angle, self.iterations)
+    }
+}
+
+/*
+sin function approximation with CORDIC in rotation mode,
+angle is fixed point I4F12 -8 to 7.999 res 0.00024 [rad]
+returns fixed point I2F14 -1 to +1
+like table_trig::sin_t angles beyond +-2PI return 0
+*/
+pub fn sin_c(angle: I4F12, iterations: u8) -> I2F14 {
+    sincos_c(angle, iterations).0
+}
+
+/*
+cos function approximation with CORDIC in rotation mode, see sin_c
+like table_trig::cos_t angles beyond +-2PI return 1
+*/
+pub fn cos_c(angle: I4F12, iterations: u8) -> I2F14 {
+    sincos_c(angle, iterations).1
+}
+
+/*
+sin and cos function approximation with CORDIC in rotation mode, returns (sin, cos)
+both come from the same iterations
+*/
+pub fn sincos_c(angle: I4F12, iterations: u8) -> (I2F14, I2F14) {
+    if angle <= -2 * I4F12::PI || angle >= 2 * I4F12::PI {
+        // beyond +-2PI overflow management
+        return (I2F14::ZERO, I2F14::ONE);
+    }
+    let mut z = (angle.to_bits() as i32) << (CORDIC_FRAC_NBITS - I4F12::FRAC_NBITS);
+    // -2PI to 2PI -> -PI to PI
+    if z > PI_Q28 {
+        z -= 2 * PI_Q28;
+    } else if z < -PI_Q28 {
+        z += 2 * PI_Q28;
+    }
+    // -PI to PI -> -PI/2 to PI/2, rotating by PI changes the sign of sin and cos
+    let negate = !(-FRAC_PI_2_Q28..=FRAC_PI_2_Q28).contains(&z);
+    if z > FRAC_PI_2_Q28 {
+        z -= PI_Q28;
+    } else if z < -FRAC_PI_2_Q28 {
+        z += PI_Q28;
+    }
+    let n = clamp_iterations(iterations);
+    // start from the gain compensation so the result has unit length
+    let mut x = GAIN_TABLE[n - 1];
+    let mut y = 0;
+    for (i, atan) in ATAN_TABLE.iter().take(n).enumerate() {
+        let dx = y >> i;
+        let dy = x >> i;
+        if z >= 0 {
+            x -= dx;
+            y += dy;
+            z -= atan;
+        } else {
+            x += dx;
+            y -= dy;
+            z += atan;
+        }
+    }
+    if negate {
+        x = -x;
+        y = -y;
+    }
+    (to_i2f14(y), to_i2f14(x))
+}
+
+/*
+atan2 with CORDIC in vectoring mode, angle of the vector (x, y)
+x and y are any 16 bit signed fixed point, e.g. alpha-beta components I6F10 or I2F14,
+only the ratio matters so the fractional bits do not change the result
+returns fixed point I4F12 -PI to PI [rad], 0 for the null vector
+*/
+pub fn atan2_c<T: FixedSigned<Bits = i16>>(y: T, x: T, iterations: u8) -> I4F12 {
+    polar_c(y, x, iterations).1
+}
+
+/*
+magnitude sqrt(x^2+y^2) with CORDIC in vectoring mode
+x and y are any 16 bit signed fixed point, e.g. I6F10 or I2F14
+returns the same fixed point type, limited to its maximum value,
+e.g. 31.999 for I6F10 and 1.999 for I2F14
+*/
+pub fn magnitude_c<T: FixedSigned<Bits = i16>>(y: T, x: T, iterations: u8) -> T {
+    polar_c(y, x, iterations).0
+}
+
+/*
+magnitude and angle of the vector (x, y) from the same CORDIC iterations,
+returns (magnitude, angle), see magnitude_c and atan2_c
+the raw bits are used as CORDIC values with POLAR_SHIFT fractional bits more,
+the largest 16 bit vector is then 2.83 and the gain does not overflow
+*/
+pub fn polar_c<T: FixedSigned<Bits = i16>>(y: T, x: T, iterations: u8) -> (T, I4F12) {
+    let (magnitude, angle) = vectoring((x.to_bits() as i32) << POLAR_SHIFT, (y.to_bits() as i32) << POLAR_SHIFT, iterations);
+    let magnitude = round_shift(magnitude, POLAR_SHIFT).clamp(i16::MIN as i32, i16::MAX as i32);
+    (T::from_bits(magnitude as i16), I4F12::from_bits(round_shift(angle, CORDIC_FRAC_NBITS - I4F12::FRAC_NBITS) as i16))
+}
+
+/*
+CORDIC vectoring mode, rotates (x, y) on the positive x axis
+x, y and the results have CORDIC_FRAC_NBITS fractional bits, |(x, y)| must be below 4.8
+so that the gain of 1.65 does not overflow
+returns (magnitude, angle), angle -PI to PI
+*/
+fn vectoring(x: i32, y: i32, iterations: u8) -> (i32, i32) {
+    if x == 0 && y == 0 {
+        // null vector, no direction
+        return (0, 0);
+    }
+    // left half plane, rotate by PI so the iterations converge
+    let (mut x, mut y, mut z) = if x < 0 {
+        (-x, -y, if y >= 0 { PI_Q28 } else { -PI_Q28 })
+    } else {
+        (x, y, 0)
+    };
+    let n = clamp_iterations(iterations);
+    for (i, atan) in ATAN_TABLE.iter().take(n).enumerate() {
+        let dx = y >> i;
+        let dy = x >> i;
+        if y > 0 {
+            x += dx;
+            y -= dy;
+            z += atan;
+        } else {
+            x -= dx;
+            y += dy;
+            z -= atan;
+        }
+    }
+    // remove the gain of the iterations
+    let magnitude = ((x as i64 * GAIN_TABLE[n - 1] as i64) >> CORDIC_FRAC_NBITS) as i32;
+    (magnitude, z)
+}
+
+fn clamp_iterations(iterations: u8) -> usize {
+    iterations.clamp(1, CORDIC_MAX_ITERATIONS) as usize
+}
+
+/*
+shift right rounding to nearest
+*/
+fn round_shift(value: i32, shift: u32) -> i32 {
+    (value + (1 << (shift - 1))) >> shift
+}
+
+/*
+CORDIC_FRAC_NBITS fixed point to I2F14, limited to the I2F14 range
+*/
+fn to_i2f14(value: i32) -> I2F14 {
+    let bits = round_shift(value, CORDIC_FRAC_NBITS - I2F14::FRAC_NBITS);
+    I2F14::from_bits(bits.clamp(i16::MIN as i32, i16::MAX as i32) as i16)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fixed::types::I6F10;
+
+    const ITERATIONS: u8 = 16;
+    // one I4F12 step for the angle, one raw step for the magnitude
+    const ANGLE_TOLERANCE: f64 = 1.0 / 4096.0;
+    const MAGNITUDE_TOLERANCE: f64 = 1.0;
+
+    /*
+    checks atan2_c and magnitude_c of the raw vector (x_bits, y_bits) against f64,
+    the angle of the null vector is 0
+    */
+    fn check<T: FixedSigned<Bits = i16>>(x_bits: i16, y_bits: i16) {
+        let (x, y) = (T::from_bits(x_bits), T::from_bits(y_bits));
+        let (x_f, y_f) = (x_bits as f64, y_bits as f64);
+        let angle = atan2_c(y, x, ITERATIONS);
+        let magnitude = magnitude_c(y, x, ITERATIONS);
+        assert_eq!((magnitude, angle), polar_c(y, x, ITERATIONS));
+        let angle_error = (angle.to_num::<f64>() - y_f.atan2(x_f)).abs();
+        // PI and -PI are the same direction
+        let angle_error = angle_error.min((angle_error - 2.0 * core::f64::consts::PI).abs());
+        assert!(angle_error <= ANGLE_TOLERANCE, "atan2({}, {}) = {}, error {}", y, x, angle, angle_error);
+        let magnitude_f = x_f.hypot(y_f).min(i16::MAX as f64);
+        let magnitude_error = (magnitude.to_bits() as f64 - magnitude_f).abs();
+        assert!(magnitude_error <= MAGNITUDE_TOLERANCE, "magnitude({}, {}) = {}, error {}", y, x, magnitude, magnitude_error);
+    }
+
+    #[test]
+    fn small_vectors() {
+        // every vector of the smallest raw values, where the quantization is worst
+        for x_bits in -256..=256 {
+            for y_bits in -256..=256 {
+                check::<I2F14>(x_bits, y_bits);
+            }
+        }
+    }
+
+    #[test]
+    fn axes_and_diagonals() {
+        // every raw value on the axes and on the diagonals, the limits of the range
+        for bits in i16::MIN..=i16::MAX {
+            check::<I2F14>(bits, 0);
+            check::<I2F14>(0, bits);
+            check::<I2F14>(bits, bits);
+            check::<I2F14>(bits, bits.saturating_neg());
+        }
+    }
+
+    #[test]
+    fn full_range_grid() {
+        // every vector on a grid covering the whole range, 61 is prime so the grid
+        // does not follow the binary steps of the CORDIC
+        for x_bits in (i16::MIN..=i16::MAX).step_by(61) {
+            for y_bits in (i16::MIN..=i16::MAX).step_by(61) {
+                check::<I2F14>(x_bits, y_bits);
+            }
+        }
+    }
+
+    #[test]
+    fn alpha_beta_types() {
+        // I6F10 and I2F14 with the same raw bits have the same angle and raw magnitude
+        for (x_bits, y_bits) in [(1000, 0), (-1000, 0), (0, 1000), (700, -700), (32767, 32767), (-32768, -32768), (1, -1)] {
+            check::<I6F10>(x_bits, y_bits);
+            let (magnitude, angle) = polar_c(I6F10::from_bits(y_bits), I6F10::from_bits(x_bits), ITERATIONS);
+            let (magnitude_2, angle_2) = polar_c(I2F14::from_bits(y_bits), I2F14::from_bits(x_bits), ITERATIONS);
+            assert_eq!((magnitude.to_bits(), angle), (magnitude_2.to_bits(), angle_2));
+        }
+        // volts in I6F10, the magnitude is limited to the maximum
+        let magnitude = magnitude_c(I6F10::from_num(4), I6F10::from_num(3), ITERATIONS);
+        assert!((magnitude.to_num::<f64>() - 5.0).abs() <= 1.0 / 1024.0);
+        assert_eq!(magnitude_c(I6F10::from_num(30), I6F10::from_num(30), ITERATIONS), I6F10::MAX);
+        assert_eq!(atan2_c(I6F10::ZERO, I6F10::ZERO, ITERATIONS), I4F12::ZERO);
+    }
+    #[test]
+    fn sin_cos_every_angle() {
+        // every I4F12 angle inside +-2PI against f64, within the 2^-14 of I2F14
+        let cordic = Cordic { iterations: ITERATIONS };
+        for bits in (-2 * I4F12::PI).to_bits() + 1..(2 * I4F12::PI).to_bits() {
+            let angle = I4F12::from_bits(bits);
+            let (sin, cos) = sincos_c(angle, ITERATIONS);
+            assert_eq!((sin_c(angle, ITERATIONS), cos_c(angle, ITERATIONS)), (sin, cos), "{}", angle);
+            assert_eq!((cordic.sin(angle), cordic.cos(angle), cordic.sincos(angle)), (sin, cos, (sin, cos)), "{}", angle);
+            let angle_f = angle.to_num::<f64>();
+            assert!((sin.to_num::<f64>() - angle_f.sin()).abs() <= 1.0 / 16384.0, "sin {} = {}", angle, sin);
+            assert!((cos.to_num::<f64>() - angle_f.cos()).abs() <= 1.0 / 16384.0, "cos {} = {}", angle, cos);
+        }
+    }
+
+    #[test]
+    fn sin_cos_beyond_2pi() {
+        // like sin_t and cos_t, from +-2PI to the I4F12 limits sin is 0 and cos is 1
+        for angle in [2 * I4F12::PI, -2 * I4F12::PI, I4F12::from_num(7), I4F12::MAX, I4F12::MIN] {
+            assert_eq!(sincos_c(angle, ITERATIONS), (I2F14::ZERO, I2F14::ONE), "{}", angle);
+            assert_eq!((sin_c(angle, ITERATIONS), cos_c(angle, ITERATIONS)), (I2F14::ZERO, I2F14::ONE), "{}", angle);
+        }
+    }
+
+    #[test]
+    fn iterations_clamped() {
+        // 0 iterations is 1 and more than CORDIC_MAX_ITERATIONS is CORDIC_MAX_ITERATIONS,
+        // neither reads GAIN_TABLE out of range
+        for angle in [I4F12::ZERO, I4F12::FRAC_PI_4, -I4F12::FRAC_PI_3, I4F12::PI, I4F12::from_num(-6)] {
+            assert_eq!(sincos_c(angle, 0), sincos_c(angle, 1), "{}", angle);
+            for iterations in [CORDIC_MAX_ITERATIONS + 1, u8::MAX] {
+                assert_eq!(sincos_c(angle, iterations), sincos_c(angle, CORDIC_MAX_ITERATIONS), "{} {}", angle, iterations);
+            }
+        }
+        let (y, x) = (I2F14::from_num(0.5), I2F14::from_num(-0.25));
+        assert_eq!(polar_c(y, x, 0), polar_c(y, x, 1));
+        assert_eq!(polar_c(y, x, u8::MAX), polar_c(y, x, CORDIC_MAX_ITERATIONS));
+    }
+}
@@ -1,15 +1,63 @@
 /*
 This file will calculate Sin anc Cos functions using an approximate fixed table
 and will plot te error compared with builtin float implementation
+the error is plotted for the left sample lookup (blue), for the
+linear interpolated lookup (green), for CORDIC (red) and for the minimax
+polynomials (magenta)
+the flash used by each method and an accuracy report over every I4F12 angle in
+-2PI to 2PI are printed: max absolute error, RMS error, mean bias and a histogram of
+the error, also written to accuracy.csv and accuracy.json
+tables for every division_shift from 0 to SWEEP_MAX_SHIFT are created in memory and
+their bytes against max and RMS error, left sample and interpolated, left edge and error
+optimised (sin_table::TableMode), are charted in table_sweep.png and written to
+table_sweep.csv, together with CORDIC and polynomials, the improvement of the
+optimised tables over the left edge ones is printed
+
+usage: SIN_Table_Verify [--max-error <e>] [--max-rms <e>] [--max-bias <e>]
+the thresholds apply to the table functions sin_t and cos_t, when one is exceeded
+the exit code is 1, so table changes can be checked automatically
 */
 
 use fixed::types::I4F12;
 use plotters::prelude::*;
 mod sin_table;
 mod table_trig;
+mod cordic;
+mod poly_trig;
+mod electrical_angle;
+mod bam_sin_table;
+mod bam_trig;
+mod accuracy;
+mod sweep;
+use table_trig::Trig;
+
+// CORDIC iterations used for the comparison
+const CORDIC_ITERATIONS: u8 = 16;
+// polynomial order used for the plots
+const POLY_ORDER: poly_trig::PolyOrder = poly_trig::PolyOrder::Order3;
+// largest division_shift of the table size sweep, 12-10 -> 2^-2 rad, 7 points
+const SWEEP_MAX_SHIFT: u16 = 10;
+
+/*
+thresholds of the table functions, None is not checked
+*/
+#[derive(Debug, Default)]
+struct Thresholds {
+    max_error: Option<f64>,
+    max_rms: Option<f64>,
+    max_bias: Option<f64>,
+}
 
 fn main() -> std::io::Result<()>{
 
+    let thresholds = match parse_args(std::env::args().skip(1)) {
+        Ok(thresholds) => thresholds,
+        Err(error) => {
+            eprintln!("{}\nusage: SIN_Table_Verify [--max-error <e>] [--max-rms <e>] [--max-bias <e>]",error);
+            std::process::exit(2)
+        }
+    };
+
     let root_drawing_area = BitMapBackend::new("plot.png", (1024, 768))
     .into_drawing_area();
 
@@ -58,7 +106,7 @@ fn main() -> std::io::Result<()>{
     &RED
     )).unwrap();
     chart.draw_series(LineSeries::new(
-        (-628..628).map(|x| x as f64 / 100.0).map(|x| (x, f64::from(table_trig::sin_t(I4F12::from_num(x),&sin_table::SIN_TABLE,12-7)))),
+        (-628..628).map(|x| x as f64 / 100.0).map(|x| (x, f64::from(table_trig::sin_t(I4F12::from_num(x),&sin_table::TABLE)))),
         &BLUE
         )).unwrap();
     chart.draw_series(LineSeries::new(
@@ -66,17 +114,148 @@ fn main() -> std::io::Result<()>{
         &RED
         )).unwrap();
     chart.draw_series(LineSeries::new(
-        (-628..628).map(|x| x as f64 / 100.0).map(|x| (x, f64::from(table_trig::cos_t(I4F12::from_num(x),&sin_table::SIN_TABLE,12-7)))),
+        (-628..628).map(|x| x as f64 / 100.0).map(|x| (x, f64::from(table_trig::cos_t(I4F12::from_num(x),&sin_table::TABLE)))),
         &BLUE
         )).unwrap();
     chart_1.draw_series(LineSeries::new(
-        (-628..628).map(|x| x as f64 / 100.0).map(|x| (x, x.sin()-f64::from(table_trig::sin_t(I4F12::from_num(x),&sin_table::SIN_TABLE,12-7)))),
+        (-628..628).map(|x| x as f64 / 100.0).map(|x| (x, x.sin()-f64::from(table_trig::sin_t(I4F12::from_num(x),&sin_table::TABLE)))),
         &BLUE
-        )).unwrap();
+        )).unwrap()
+        .label("left sample")
+        .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], BLUE));
+    chart_1.draw_series(LineSeries::new(
+        (-628..628).map(|x| x as f64 / 100.0).map(|x| (x, x.sin()-f64::from(table_trig::sin_t_interp(I4F12::from_num(x),&sin_table::TABLE)))),
+        &GREEN
+        )).unwrap()
+        .label("interpolated")
+        .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], GREEN));
+    chart_1.draw_series(LineSeries::new(
+        (-628..628).map(|x| x as f64 / 100.0).map(|x| (x, x.sin()-f64::from(cordic::sin_c(I4F12::from_num(x),CORDIC_ITERATIONS)))),
+        &RED
+        )).unwrap()
+        .label("cordic")
+        .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], RED));
+    chart_1.draw_series(LineSeries::new(
+        (-628..628).map(|x| x as f64 / 100.0).map(|x| (x, x.sin()-f64::from(poly_trig::sin_p(I4F12::from_num(x),POLY_ORDER)))),
+        &MAGENTA
+        )).unwrap()
+        .label("polynomial")
+        .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], MAGENTA));
+    chart_1.configure_series_labels()
+    .background_style(WHITE)
+    .border_style(BLACK)
+    .draw()
+    .unwrap();
     chart_2.draw_series(LineSeries::new(
-        (-628..628).map(|x| x as f64 / 100.0).map(|x| (x, x.cos()-f64::from(table_trig::cos_t(I4F12::from_num(x),&sin_table::SIN_TABLE,12-7)))),
+        (-628..628).map(|x| x as f64 / 100.0).map(|x| (x, x.cos()-f64::from(table_trig::cos_t(I4F12::from_num(x),&sin_table::TABLE)))),
         &BLUE
-        )).unwrap();
+        )).unwrap()
+        .label("left sample")
+        .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], BLUE));
+    chart_2.draw_series(LineSeries::new(
+        (-628..628).map(|x| x as f64 / 100.0).map(|x| (x, x.cos()-f64::from(table_trig::cos_t_interp(I4F12::from_num(x),&sin_table::TABLE)))),
+        &GREEN
+        )).unwrap()
+        .label("interpolated")
+        .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], GREEN));
+    chart_2.draw_series(LineSeries::new(
+        (-628..628).map(|x| x as f64 / 100.0).map(|x| (x, x.cos()-f64::from(cordic::cos_c(I4F12::from_num(x),CORDIC_ITERATIONS)))),
+        &RED
+        )).unwrap()
+        .label("cordic")
+        .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], RED));
+    chart_2.draw_series(LineSeries::new(
+        (-628..628).map(|x| x as f64 / 100.0).map(|x| (x, x.cos()-f64::from(poly_trig::cos_p(I4F12::from_num(x),POLY_ORDER)))),
+        &MAGENTA
+        )).unwrap()
+        .label("polynomial")
+        .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], MAGENTA));
+    chart_2.configure_series_labels()
+    .background_style(WHITE)
+    .border_style(BLACK)
+    .draw()
+    .unwrap();
+
+    // accuracy over every I4F12 angle in -2PI to 2PI and flash used by each method
+    let table = table_trig::TableTrig{table:sin_table::TABLE, interpolate:false};
+    let table_interp = table_trig::TableTrig{table:sin_table::TABLE, interpolate:true};
+    let cordic = cordic::Cordic{iterations:CORDIC_ITERATIONS};
+    let poly_3 = poly_trig::Poly{order:poly_trig::PolyOrder::Order3};
+    let poly_5 = poly_trig::Poly{order:poly_trig::PolyOrder::Order5};
+    let poly_7 = poly_trig::Poly{order:poly_trig::PolyOrder::Order7};
+    let bam = bam_trig::BamTrig{table:bam_sin_table::BAM_TABLE};
+    let table_bytes = std::mem::size_of_val(&sin_table::SIN_TABLE);
+    println!("table: {} samples with {} fractional bits from 0 to {} rad, division_shift {}, {:?}", sin_table::TABLE.data().len(),
+    sin_table::TABLE.output_frac_nbits(), sin_table::TABLE.end_angle(), sin_table::TABLE.division_shift(), sin_table::TABLE_MODE);
+    let methods: [(&str, &dyn Trig, usize); 7] = [
+        ("table", &table, table_bytes),
+        ("table interpolated", &table_interp, table_bytes),
+        ("cordic", &cordic, cordic::CORDIC_FLASH_BYTES),
+        ("polynomial order 3", &poly_3, poly_3.order.flash_bytes()),
+        ("polynomial order 5", &poly_5, poly_5.order.flash_bytes()),
+        ("polynomial order 7", &poly_7, poly_7.order.flash_bytes()),
+        ("binary angle table", &bam, std::mem::size_of_val(bam_sin_table::BAM_TABLE.data())),
+    ];
+    let mut results = Vec::new();
+    for (name, method, flash_bytes) in methods {
+        println!("{}: flash {} bytes", name, flash_bytes);
+        let sin_stats = accuracy::error_stats(|angle| method.sin(angle), f64::sin);
+        let cos_stats = accuracy::error_stats(|angle| method.cos(angle), f64::cos);
+        accuracy::print_report(name, "sin", &sin_stats);
+        accuracy::print_report(name, "cos", &cos_stats);
+        results.push((name.to_string(), "sin", sin_stats));
+        results.push((name.to_string(), "cos", cos_stats));
+    }
+    accuracy::write_csv(&mut std::fs::File::create("accuracy.csv")?, &results)?;
+    accuracy::write_json(&mut std::fs::File::create("accuracy.json")?, &results)?;
+
+    // table size sweep, the backends without table are single points
+    let points = sweep::table_sweep(0..=SWEEP_MAX_SHIFT, &methods[2..]);
+    for point in points.iter() {
+        let division_shift = point.division_shift.map_or("-".to_string(), |shift| shift.to_string());
+        println!("sweep {} shift {}: {} bytes, sin max {:.6} rms {:.6}, cos max {:.6} rms {:.6}",
+        point.method, division_shift, point.bytes, point.sin.max_abs, point.sin.rms, point.cos.max_abs, point.cos.rms);
+    }
+    sweep::print_improvement(&points);
+    sweep::write_csv(&mut std::fs::File::create("table_sweep.csv")?, &points)?;
+    sweep::draw_chart("table_sweep.png", &points);
+
+    // thresholds on sin_t and cos_t, the first two results
+    let mut failed = false;
+    for (name, function, stats) in results.iter().take(2) {
+        let checks = [
+            ("max error", stats.max_abs, thresholds.max_error),
+            ("rms error", stats.rms, thresholds.max_rms),
+            ("mean bias", stats.mean.abs(), thresholds.max_bias),
+        ];
+        for (check, value, threshold) in checks {
+            if threshold.is_some_and(|threshold| value > threshold) {
+                println!("FAIL {} {}: {} {:.6} above {}", name, function, check, value, threshold.unwrap_or_default());
+                failed = true;
+            }
+        }
+    }
+    if failed {
+        std::process::exit(1);
+    }
         Ok(())
 
 }
+
+/*
+parses the threshold options
+*/
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Thresholds, String> {
+    let mut thresholds = Thresholds::default();
+    while let Some(arg) = args.next() {
+        let threshold = match arg.as_str() {
+            "--max-error" => &mut thresholds.max_error,
+            "--max-rms" => &mut thresholds.max_rms,
+            "--max-bias" => &mut thresholds.max_bias,
+            _ => return Err(format!("unknown option {}", arg)),
+        };
+        let value = args.next().ok_or(format!("missing value for {}", arg))?;
+        *threshold = Some(value.parse().map_err(|_| format!("invalid value {} for {}", value, arg))?);
+    }
+    Ok(thresholds)
+}
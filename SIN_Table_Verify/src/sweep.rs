@@ -0,0 +1,165 @@
+use std::io::prelude::*;
+use fixed::types::I2F14;
+use fixed::types::I4F12;
+use plotters::prelude::*;
+use super::accuracy::{self, ErrorStats};
+use super::sin_table::{self, TableMode};
+use super::table_trig::{SinTable, TableTrig, Trig, MAX_REDUCED_ANGLE};
+
+/*
+accuracy of one method with one table size
+*/
+pub struct SweepPoint {
+    pub method: String,
+    pub division_shift: Option<u16>,
+    pub bytes: usize,
+    pub sin: ErrorStats,
+    pub cos: ErrorStats,
+}
+
+impl SweepPoint {
+    fn new(method: &str, division_shift: Option<u16>, bytes: usize, trig: &dyn Trig) -> Self {
+        SweepPoint {
+            method: method.to_string(),
+            division_shift,
+            bytes,
+            sin: accuracy::error_stats(|angle| trig.sin(angle), f64::sin),
+            cos: accuracy::error_stats(|angle| trig.cos(angle), f64::cos),
+        }
+    }
+
+    /*
+    worst of sin and cos
+    */
+    fn max_abs(&self) -> f64 {
+        self.sin.max_abs.max(self.cos.max_abs)
+    }
+
+    /*
+    rms of sin and cos together
+    */
+    fn rms(&self) -> f64 {
+        ((self.sin.rms * self.sin.rms + self.cos.rms * self.cos.rms) / 2.0).sqrt()
+    }
+}
+
+// table methods of the sweep: name, how the table is created, interpolated lookup
+const TABLE_METHODS: [(&str, TableMode, bool); 6] = [
+    ("table", TableMode::LeftEdge, false),
+    ("table minimax", TableMode::NearestMinimax, false),
+    ("table rms", TableMode::NearestRms, false),
+    ("table interpolated", TableMode::LeftEdge, true),
+    ("table interpolated minimax", TableMode::InterpolatedMinimax, true),
+    ("table interpolated rms", TableMode::InterpolatedRms, true),
+];
+
+/*
+creates the tables for each division_shift with the same algorithm of sin_table::SIN_TABLE,
+left edge and error optimised, and evaluates sin_t/cos_t or the interpolated versions
+with them, then adds the other backends, with the flash they use, as single points
+*/
+pub fn table_sweep(division_shifts: impl Iterator<Item = u16>, others: &[(&str, &dyn Trig, usize)]) -> Vec<SweepPoint> {
+    let mut points = Vec::new();
+    for division_shift in division_shifts {
+        for (name, mode, interpolate) in TABLE_METHODS {
+            let mut data = vec![0; SinTable::required_len(division_shift)];
+            sin_table::fill_table(&mut data, I4F12::FRAC_NBITS, I2F14::FRAC_NBITS, division_shift, mode);
+            let table = SinTable::new(&data, division_shift, I2F14::FRAC_NBITS, MAX_REDUCED_ANGLE);
+            let bytes = std::mem::size_of_val(data.as_slice());
+            points.push(SweepPoint::new(name, Some(division_shift), bytes, &TableTrig{table, interpolate}));
+        }
+    }
+    for (name, trig, bytes) in others {
+        points.push(SweepPoint::new(name, None, *bytes, *trig));
+    }
+    points
+}
+
+/*
+improvement of the error optimised tables over the left edge ones with the same
+division_shift and lookup, one line per table: max and rms error ratio, left / optimised
+*/
+pub fn print_improvement(points: &[SweepPoint]) {
+    for (name, mode, interpolate) in TABLE_METHODS {
+        if mode == TableMode::LeftEdge {
+            continue;
+        }
+        let left_name = if interpolate { "table interpolated" } else { "table" };
+        for point in points.iter().filter(|point| point.method == name) {
+            if let Some(left) = points.iter().find(|left| left.method == left_name && left.division_shift == point.division_shift) {
+                println!("{} shift {}: max error {:.6} -> {:.6} ({:.2}x), rms {:.6} -> {:.6} ({:.2}x)",
+                name, point.division_shift.unwrap_or(0), left.max_abs(), point.max_abs(), left.max_abs() / point.max_abs(),
+                left.rms(), point.rms(), left.rms() / point.rms());
+            }
+        }
+    }
+}
+
+/*
+CSV with one line per method and table size
+*/
+pub fn write_csv(fileh: &mut impl Write, points: &[SweepPoint]) -> std::io::Result<()> {
+    writeln!(fileh,"method,division_shift,bytes,sin_max_abs,sin_rms,cos_max_abs,cos_rms")?;
+    for point in points {
+        let division_shift = point.division_shift.map_or(String::new(), |shift| shift.to_string());
+        writeln!(fileh,"{},{},{},{},{},{},{}",point.method,division_shift,point.bytes,point.sin.max_abs,point.sin.rms,point.cos.max_abs,point.cos.rms)?;
+    }
+    Ok(())
+}
+
+/*
+chart of table bytes against max and RMS error, both axes logarithmic,
+the tables are lines, the other backends single points
+*/
+pub fn draw_chart(file_name: &str, points: &[SweepPoint]) {
+    let root_drawing_area = BitMapBackend::new(file_name, (1024, 768))
+    .into_drawing_area();
+    root_drawing_area.fill(&WHITE).unwrap();
+
+    let mut chart = ChartBuilder::on(&root_drawing_area)
+    .caption("Error versus flash bytes",("sans-serif", 40).into_font())
+    .margin(10)
+    .x_label_area_size(40)
+    .y_label_area_size(60)
+    .build_cartesian_2d((8f64..20000f64).log_scale(), (1e-5f64..1.0f64).log_scale())
+    .unwrap();
+    chart.configure_mesh()
+    .x_desc("bytes")
+    .y_desc("error")
+    .draw()
+    .unwrap();
+
+    let dark_green = RGBColor(0, 128, 0);
+    let orange = RGBColor(255, 128, 0);
+    let purple = RGBColor(128, 0, 128);
+    let lines: [(&str, RGBColor, RGBColor); 4] = [
+        ("table", BLUE, CYAN),
+        ("table minimax", orange, YELLOW),
+        ("table interpolated", dark_green, GREEN),
+        ("table interpolated minimax", purple, RGBColor(200, 150, 255)),
+    ];
+    for (method, max_color, rms_color) in lines {
+        let line: Vec<&SweepPoint> = points.iter().filter(|point| point.method == method).collect();
+        chart.draw_series(LineSeries::new(line.iter().map(|point| (point.bytes as f64, point.max_abs())), max_color)).unwrap()
+        .label(format!("{} max", method))
+        .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], max_color));
+        chart.draw_series(LineSeries::new(line.iter().map(|point| (point.bytes as f64, point.rms())), rms_color)).unwrap()
+        .label(format!("{} rms", method))
+        .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], rms_color));
+        // division_shift next to each point
+        chart.draw_series(line.iter().map(|point| Text::new(format!("{}", point.division_shift.unwrap_or(0)), (point.bytes as f64, point.max_abs()), ("sans-serif", 14).into_font()))).unwrap();
+    }
+    for point in points.iter().filter(|point| point.division_shift.is_none()) {
+        let color = if point.method.starts_with("cordic") { RED } else { MAGENTA };
+        chart.draw_series([
+            Circle::new((point.bytes as f64, point.max_abs()), 4, color.filled()),
+            Circle::new((point.bytes as f64, point.rms()), 4, color),
+        ]).unwrap();
+        chart.draw_series([Text::new(point.method.clone(), (point.bytes as f64, point.max_abs()), ("sans-serif", 14).into_font())]).unwrap();
+    }
+    chart.configure_series_labels()
+    .background_style(WHITE)
+    .border_style(BLACK)
+    .draw()
+    .unwrap();
+}
@@ -0,0 +1,205 @@
+use core::ops::{Add, AddAssign, Sub, SubAssign};
+use fixed::types::I4F12;
+
+/*
+electrical angle as a binary angle, u16 full scale is one turn:
+0 is 0 rad, 16384 is PI/2, 32768 is PI, 49152 is 3/2PI, 65536 wraps to 0
+resolution 2PI/65536 = 0.000096 rad, better than I4F12 0.00024 rad
+all the operations wrap on overflow, so the angle can be incremented forever
+and never falls outside the -2PI to 2PI range accepted by the trig functions
+*/
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ElectricalAngle(u16);
+
+// 2PI in I4F12 bits (2PI*4096) and 65536/(2PI*4096) scaled by 2^16, for bit conversions
+const TWO_PI_BITS: i32 = 25736;
+const BITS_PER_RAD_BITS: i32 = 166886;
+
+impl ElectricalAngle {
+    pub const ZERO: ElectricalAngle = ElectricalAngle(0);
+
+    pub const fn from_bits(bits: u16) -> ElectricalAngle {
+        ElectricalAngle(bits)
+    }
+
+    pub const fn to_bits(self) -> u16 {
+        self.0
+    }
+
+    /*
+    converts an angle in radians, any I4F12 value -8 to 7.999 [rad]
+    is wrapped into one turn
+    */
+    pub fn from_radians(angle: I4F12) -> ElectricalAngle {
+        // only the lower 16 bits of the result are needed, they are a whole number of
+        // turns away from the exact value so the wrapping multiplication is harmless
+        let bits = (angle.to_bits() as i32)
+            .wrapping_mul(BITS_PER_RAD_BITS)
+            .wrapping_add(1 << 15)
+            >> 16;
+        ElectricalAngle(bits as u16)
+    }
+
+    /*
+    converts to radians in the -PI to PI range, fixed point I4F12
+    */
+    pub fn to_radians(self) -> I4F12 {
+        let bits = (self.0 as i16 as i32 * TWO_PI_BITS + (1 << 15)) >> 16;
+        I4F12::from_bits(bits as i16)
+    }
+}
+
+impl From<ElectricalAngle> for I4F12 {
+    fn from(angle: ElectricalAngle) -> I4F12 {
+        angle.to_radians()
+    }
+}
+
+impl Add for ElectricalAngle {
+    type Output = ElectricalAngle;
+    fn add(self, rhs: ElectricalAngle) -> ElectricalAngle {
+        ElectricalAngle(self.0.wrapping_add(rhs.0))
+    }
+}
+
+impl Sub for ElectricalAngle {
+    type Output = ElectricalAngle;
+    fn sub(self, rhs: ElectricalAngle) -> ElectricalAngle {
+        ElectricalAngle(self.0.wrapping_sub(rhs.0))
+    }
+}
+
+impl AddAssign for ElectricalAngle {
+    fn add_assign(&mut self, rhs: ElectricalAngle) {
+        *self = *self + rhs;
+    }
+}
+
+impl SubAssign for ElectricalAngle {
+    fn sub_assign(&mut self, rhs: ElectricalAngle) {
+        *self = *self - rhs;
+    }
+}
+
+/*
+adds an increment in radians, i.e. speed [rad/s] * dt [s]
+*/
+impl Add<I4F12> for ElectricalAngle {
+    type Output = ElectricalAngle;
+    fn add(self, rhs: I4F12) -> ElectricalAngle {
+        self + ElectricalAngle::from_radians(rhs)
+    }
+}
+
+/*
+subtracts an increment in radians, i.e. speed [rad/s] * dt [s]
+*/
+impl Sub<I4F12> for ElectricalAngle {
+    type Output = ElectricalAngle;
+    fn sub(self, rhs: I4F12) -> ElectricalAngle {
+        self - ElectricalAngle::from_radians(rhs)
+    }
+}
+
+impl AddAssign<I4F12> for ElectricalAngle {
+    fn add_assign(&mut self, rhs: I4F12) {
+        *self = *self + rhs;
+    }
+}
+
+impl SubAssign<I4F12> for ElectricalAngle {
+    fn sub_assign(&mut self, rhs: I4F12) {
+        *self = *self - rhs;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::f64::consts::PI;
+
+    fn radians(angle: ElectricalAngle) -> f64 {
+        angle.to_radians().to_num()
+    }
+
+    /*
+    difference of two angles [rad] wrapped to -PI to PI
+    */
+    fn wrapped(difference: f64) -> f64 {
+        (difference + PI).rem_euclid(2.0 * PI) - PI
+    }
+
+    #[test]
+    fn wraps_at_pi() {
+        let step = ElectricalAngle::from_bits(1);
+        let below_pi = ElectricalAngle::from_bits(32767);
+        // +PI and -PI are the same angle, to_radians gives -PI
+        assert_eq!(below_pi + step, ElectricalAngle::from_bits(32768));
+        assert!(radians(below_pi) > 3.1 && radians(below_pi + step) < -3.1);
+        assert_eq!(below_pi + step - step, below_pi);
+        // and one turn wraps to 0
+        assert_eq!(ElectricalAngle::from_bits(65535) + step, ElectricalAngle::ZERO);
+        assert_eq!(ElectricalAngle::ZERO - step, ElectricalAngle::from_bits(65535));
+        // increments in radians across PI and -PI
+        let angle = ElectricalAngle::from_radians(I4F12::from_num(3.1)) + I4F12::from_num(0.1);
+        assert!((radians(angle) - (3.2 - 2.0 * PI)).abs() <= 0.001, "{}", angle.to_radians());
+        let angle = ElectricalAngle::from_radians(I4F12::from_num(-3.1)) - I4F12::from_num(0.1);
+        assert!((radians(angle) - (2.0 * PI - 3.2)).abs() <= 0.001, "{}", angle.to_radians());
+    }
+
+    #[test]
+    fn assign_same_as_operators() {
+        for bits in (0..=u16::MAX).step_by(251) {
+            let angle = ElectricalAngle::from_bits(bits);
+            for (step, step_rad) in [(ElectricalAngle::from_bits(40000), I4F12::from_num(2.5)), (ElectricalAngle::from_bits(17), I4F12::from_num(-7.9))] {
+                let mut sum = angle;
+                sum += step;
+                assert_eq!(sum, angle + step);
+                let mut difference = angle;
+                difference -= step;
+                assert_eq!(difference, angle - step);
+                let mut sum = angle;
+                sum += step_rad;
+                assert_eq!(sum, angle + step_rad);
+                let mut difference = angle;
+                difference -= step_rad;
+                assert_eq!(difference, angle - step_rad);
+            }
+        }
+    }
+
+    #[test]
+    fn radians_round_trip() {
+        // every I4F12 angle, also beyond +-2PI, comes back wrapped into -PI to PI within
+        // one I4F12 step, the binary angle is 2.5 times finer
+        let delta = I4F12::DELTA.to_num::<f64>();
+        for bits in i16::MIN..=i16::MAX {
+            let angle = I4F12::from_bits(bits);
+            let back = radians(ElectricalAngle::from_radians(angle));
+            assert!((-PI - delta..=PI + delta).contains(&back), "{} -> {}", angle, back);
+            assert!(wrapped(back - angle.to_num::<f64>()).abs() <= delta, "{} -> {}", angle, back);
+        }
+        // every binary angle comes back within the 1.3 binary steps of half an I4F12 step
+        for bits in 0..=u16::MAX {
+            let angle = ElectricalAngle::from_bits(bits);
+            let difference = ElectricalAngle::from_radians(angle.to_radians()).to_bits().wrapping_sub(bits) as i16;
+            assert!(difference.abs() <= 2, "{:?} -> {}", angle, difference);
+        }
+    }
+
+    #[test]
+    fn into_i4f12_edges() {
+        // rounded to nearest, PI/2 is 6434 bits where I4F12::FRAC_PI_2 is truncated to 6433
+        for (bits, expected) in [(0, 0), (1, 0), (16384, 6434), (32767, 12868), (32768, -12868), (49152, -6434), (65535, 0)] {
+            assert_eq!(I4F12::from(ElectricalAngle::from_bits(bits)), I4F12::from_bits(expected), "{}", bits);
+        }
+        // at every binary angle, rounded to nearest plus the error of the rounded
+        // TWO_PI_BITS, up to 0.035 steps
+        for bits in 0..=u16::MAX {
+            let angle = ElectricalAngle::from_bits(bits);
+            let expected = wrapped(bits as f64 / 65536.0 * 2.0 * PI);
+            let expected = if bits == 32768 { -PI } else { expected };
+            assert!((radians(angle) - expected).abs() <= 0.6 * I4F12::DELTA.to_num::<f64>(), "{}", bits);
+        }
+    }
+}
@@ -0,0 +1,280 @@
+use fixed::traits::FixedSigned;
+use fixed::types::I16F16;
+use super::frames::{AlphaBeta, PhaseDuties};
+use super::per_unit::PerUnit;
+use super::{mod_inverse_clarke, svpwm};
+
+/*
+modulation with a measured DC bus voltage Vdc: the alpha beta voltage reference in volts
+is divided by Vdc and svpwm gives the duty cycles 0 to 1, so the phase voltages follow
+the reference while the bus voltage ripples or the battery sags
+modulation index m = |V|/(Vdc/sqrt(3)), |V| the amplitude of the phase voltages, the
+linear region of svpwm is m <= 1: the circle inscribed in the hexagon, 57.7% of Vdc
+svpwm has the line to line voltages U-V V-W W-U of the i j k values, so the reference
+is scaled by sqrt(3)/Vdc to give phase voltages |V| and not |V|/sqrt(3)
+*/
+
+// sqrt(3) and the initial estimate 48/17 - 32/17*d of the reciprocal, U2F30
+const SQRT_3_Q30: u64 = 1859775393;
+const RECIPROCAL_C1_Q30: u64 = 3031741621;
+const RECIPROCAL_C2_Q30: u64 = 2021161081;
+// 3 iterations from the 1/17 error of the initial estimate are below 2^-30
+const RECIPROCAL_ITERATIONS: u32 = 3;
+const ONE_Q30: u64 = 1 << 30;
+
+/*
+measured DC bus voltage, fixed point I16F16 [V], with sqrt(3)/Vdc computed once by
+new so that every modulate of the same bus sample has only multiplications
+a voltage <= 0 is no bus: modulate gives the null vector
+*/
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DcBus {
+    voltage: I16F16,
+    // sqrt(3)/Vdc = scale*2^-shift, 0 without bus
+    scale: u32,
+    shift: u32,
+}
+
+impl DcBus {
+    pub fn new(voltage: I16F16) -> DcBus {
+        if voltage <= I16F16::ZERO {
+            return DcBus { voltage, scale: 0, shift: 0 };
+        }
+        let (reciprocal, shift) = reciprocal(voltage.to_bits() as u32);
+        let scale = ((reciprocal as u64 * SQRT_3_Q30) >> 30) as u32;
+        // the reciprocal of the raw bits times 2^16 of the I16F16 fractional bits
+        DcBus { voltage, scale, shift: shift - 16 }
+    }
+
+    pub const fn voltage(&self) -> I16F16 {
+        self.voltage
+    }
+}
+
+/*
+modulate output: the duty cycles 0 to 1 of the U V W legs, the modulation index
+reached, 0 to 1, and limited true if the reference was beyond the linear region
+*/
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Modulation {
+    pub duties: PhaseDuties<PerUnit>,
+    pub index: PerUnit,
+    pub limited: bool,
+}
+
+/*
+svpwm of the alpha beta voltage reference V [V] on the bus, e.g. from inverse_park
+V is any 16 bit signed fixed point in volts, e.g. I6F10
+beyond the linear region (m > 1) the reference is clamped on the inscribed circle,
+same angle and m = 1, and limited is set
+the duties are per unit of the PWM period, for the timer compare values see
+duties_to_compare with max PerUnit::ONE
+*/
+pub fn modulate<T: FixedSigned<Bits = i16>>(V: AlphaBeta<T>, bus: &DcBus) -> Modulation {
+    let (alpha, beta) = (V.alpha.to_bits() as i64, V.beta.to_bits() as i64);
+    if bus.scale == 0 {
+        return Modulation { limited: alpha != 0 || beta != 0, ..Modulation::default() };
+    }
+    // |V| with 16 fractional bits more than T
+    let magnitude = isqrt(((alpha * alpha + beta * beta) as u64) << 32);
+    let frac_nbits = T::FRAC_NBITS;
+    // m with 30 fractional bits, the product can take all the 64 bits
+    let index = ((((magnitude as u64 * bus.scale as u64) >> (frac_nbits + 16 + bus.shift - 30 - 1)) + 1) >> 1) as i64;
+    let (Valpha, Vbeta, index, limited) = if index <= ONE_Q30 as i64 {
+        // sqrt(3)*V/Vdc
+        let shift = frac_nbits + bus.shift - PerUnit::FRAC_NBITS;
+        let to_pu = |value: i64| round_shr(value * bus.scale as i64, shift);
+        (to_pu(alpha), to_pu(beta), index, false)
+    } else {
+        // V/|V| on the inscribed circle
+        let (reciprocal, shift) = reciprocal(magnitude);
+        let shift = shift - 16 - PerUnit::FRAC_NBITS;
+        let to_pu = |value: i64| round_shr(value * reciprocal as i64, shift);
+        (to_pu(alpha), to_pu(beta), ONE_Q30 as i64, true)
+    };
+    let Vpu = AlphaBeta::new(PerUnit::from_bits(Valpha as i16), PerUnit::from_bits(Vbeta as i16));
+    let duties = svpwm(mod_inverse_clarke(Vpu), PerUnit::ONE);
+    // the rounding of the values on the circle can go one step beyond 0 or 1
+    let clamp = |duty: PerUnit| duty.clamp(PerUnit::ZERO, PerUnit::ONE);
+    let duties = PhaseDuties { u: clamp(duties.u), v: clamp(duties.v), w: clamp(duties.w), sector: duties.sector };
+    Modulation { duties, index: PerUnit::from_bits(round_shr(index, 30 - PerUnit::FRAC_NBITS) as i16), limited }
+}
+
+/*
+reciprocal of x > 0 with Newton-Raphson iterations r = r*(2-d*r), multiplications
+only, x is shifted to d = 0.5 to 1 so that r is 1 to 2
+returns (r, shift) with 1/x = r*2^-shift, r U2F30
+*/
+fn reciprocal(x: u32) -> (u32, u32) {
+    let leading_zeros = x.leading_zeros();
+    // d with 32 fractional bits
+    let d = ((x as u64) << leading_zeros) as u32 as u64;
+    let mut r = RECIPROCAL_C1_Q30 - ((RECIPROCAL_C2_Q30 * d) >> 32);
+    for _ in 0..RECIPROCAL_ITERATIONS {
+        let dr = (d * r) >> 32;
+        r = (r * (2 * ONE_Q30 - dr)) >> 30;
+    }
+    // x = d*2^(32-leading_zeros)
+    (r as u32, 62 - leading_zeros)
+}
+
+/*
+square root rounded down, bit by bit
+*/
+fn isqrt(value: u64) -> u32 {
+    let mut rest = value;
+    let mut root: u64 = 0;
+    let mut bit: u64 = 1 << 62;
+    while bit > rest {
+        bit >>= 2;
+    }
+    while bit != 0 {
+        if rest >= root + bit {
+            rest -= root + bit;
+            root = (root >> 1) + bit;
+        } else {
+            root >>= 1;
+        }
+        bit >>= 2;
+    }
+    root as u32
+}
+
+/*
+shift right rounding to nearest, 0 for shifts beyond the 63 bits of the value
+*/
+fn round_shr(value: i64, shift: u32) -> i64 {
+    if shift >= 63 {
+        return 0;
+    }
+    (value + (1 << (shift - 1))) >> shift
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::frames::{Abc, Dq};
+    use super::super::inverse_park;
+    use fixed::types::{I4F12, I6F10};
+
+    fn volts(value: f64) -> I6F10 {
+        I6F10::from_num(value)
+    }
+
+    fn bus(value: f64) -> DcBus {
+        DcBus::new(I16F16::from_num(value))
+    }
+
+    /*
+    phase voltages of the duties: the leg voltages minus their common mode
+    */
+    fn phase_voltages(modulation: &Modulation, Vdc: f64) -> Abc<f64> {
+        let uvw = modulation.duties.uvw().map(|d| d.to_num::<f64>() * Vdc);
+        let common = (uvw.a + uvw.b + uvw.c) / 3.0;
+        uvw.map(|v| v - common)
+    }
+
+    /*
+    phase voltages of the reference, inverse_clarke in f64, the I6F10 one has the
+    0.1% error of its sqrt(3)/2
+    */
+    fn phase_reference(V: AlphaBeta<I6F10>) -> Abc<f64> {
+        let (alpha, beta) = (V.alpha.to_num::<f64>(), V.beta.to_num::<f64>());
+        let beta = 3f64.sqrt() / 2.0 * beta;
+        Abc::new(alpha, beta - alpha / 2.0, -beta - alpha / 2.0)
+    }
+
+    #[test]
+    fn reciprocal_accuracy() {
+        for x in (1..=u32::MAX).step_by(65521).chain([1, 2, 3, 0x8000_0000, 0xffff_ffff]) {
+            let (r, shift) = reciprocal(x);
+            let expected = 2f64.powi(shift as i32) / x as f64;
+            assert!((r as f64 - expected).abs() <= 4.0, "1/{} = {} expected {}", x, r, expected);
+        }
+    }
+
+    #[test]
+    fn isqrt_exact() {
+        for value in (0..1u64 << 40).step_by(1 << 25 | 12345).chain([0, 1, 2, 3, 4, u64::MAX >> 1]) {
+            let root = isqrt(value) as u64;
+            assert!(root * root <= value && (root + 1) * (root + 1) > value, "isqrt({}) = {}", value, root);
+        }
+    }
+
+    #[test]
+    fn phase_voltages_follow_reference() {
+        // in the linear region the phase voltages are the ones of the reference, for
+        // every bus voltage, within the resolution of the duties
+        for Vdc in [12.0, 18.5, 24.0, 48.0, 60.0] {
+            for bits in (-12800..12800).step_by(113) {
+                let V = inverse_park(Dq::new(volts(0.15 * Vdc), volts(0.4 * Vdc).min(I6F10::MAX)), I4F12::from_bits(bits));
+                let modulation = modulate(V, &bus(Vdc));
+                assert!(!modulation.limited);
+                let reference = phase_reference(V);
+                let phases = phase_voltages(&modulation, Vdc);
+                let tolerance = 4.0 * Vdc / 16384.0;
+                for (phase, expected) in [(phases.a, reference.a), (phases.b, reference.b), (phases.c, reference.c)] {
+                    assert!((phase - expected).abs() <= tolerance, "{}V: {:?} expected {:?}", Vdc, phases, reference);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn modulation_index() {
+        // m = sqrt(3)*|V|/Vdc, 1 at the edge of the linear region
+        for (Vd, Vq, Vdc) in [(0.0, 0.0, 24.0), (3.0, 4.0, 24.0), (0.0, 13.8, 24.0), (-6.0, 8.0, 17.33), (1.0, 0.0, 100.0)] {
+            let modulation = modulate(AlphaBeta::new(volts(Vd), volts(Vq)), &bus(Vdc));
+            let expected = 3f64.sqrt() * f64::hypot(Vd, Vq) / Vdc;
+            assert!((modulation.index.to_num::<f64>() - expected).abs() <= 2.0 / 16384.0, "{} expected {}", modulation.index, expected);
+            assert!(!modulation.limited);
+        }
+        // no voltage, the null vector
+        let zero = modulate(AlphaBeta::new(volts(0.0), volts(0.0)), &bus(24.0));
+        assert_eq!(zero.duties.sector, super::super::frames::Sector::Zero);
+    }
+
+    #[test]
+    fn sagging_bus_is_compensated() {
+        // same reference, lower bus: the duties grow so that the phase voltages stay
+        let V = inverse_park(Dq::new(volts(2.0), volts(6.0)), I4F12::from_num(0.7));
+        let full = modulate(V, &bus(24.0));
+        let sagged = modulate(V, &bus(16.0));
+        assert!(sagged.index > full.index);
+        let (full, sagged) = (phase_voltages(&full, 24.0), phase_voltages(&sagged, 16.0));
+        assert!((full.a - sagged.a).abs() < 0.01 && (full.b - sagged.b).abs() < 0.01, "{:?} {:?}", full, sagged);
+    }
+
+    #[test]
+    fn clamped_to_linear_region() {
+        // beyond Vdc/sqrt(3) the reference is clamped on the circle with the same angle
+        for bits in (-12800..12800).step_by(97) {
+            let theta = I4F12::from_bits(bits);
+            let V = inverse_park(Dq::new(volts(0.0), volts(20.0)), theta);
+            let modulation = modulate(V, &bus(24.0));
+            assert!(modulation.limited);
+            assert_eq!(modulation.index, PerUnit::ONE);
+            let uvw = modulation.duties.uvw();
+            assert!([uvw.a, uvw.b, uvw.c].iter().all(|d| *d >= PerUnit::ZERO && *d <= PerUnit::ONE), "{:?}", uvw);
+            // the phase voltages are the reference scaled to 24/sqrt(3)
+            let scale = 24.0 / 3f64.sqrt() / V.alpha.to_num::<f64>().hypot(V.beta.to_num());
+            let reference = phase_reference(V).map(|v| v * scale);
+            let phases = phase_voltages(&modulation, 24.0);
+            assert!((phases.a - reference.a).abs() <= 0.02 && (phases.b - reference.b).abs() <= 0.02, "{:?} expected {:?}", phases, reference);
+        }
+        // same angle of the unclamped reference at the limit
+        let limit = modulate(AlphaBeta::new(volts(24.0 / 3f64.sqrt() - 0.002), volts(0.0)), &bus(24.0));
+        let beyond = modulate(AlphaBeta::new(volts(31.0), volts(0.0)), &bus(24.0));
+        assert!(!limit.limited && beyond.limited);
+        assert_eq!(limit.duties.sector, beyond.duties.sector);
+        assert!((limit.duties.u - beyond.duties.u).abs() <= PerUnit::from_num(0.001));
+    }
+
+    #[test]
+    fn no_bus() {
+        for Vdc in [0.0, -5.0] {
+            let modulation = modulate(AlphaBeta::new(volts(1.0), volts(0.0)), &bus(Vdc));
+            assert_eq!((modulation.duties, modulation.index, modulation.limited), (PhaseDuties::default(), PerUnit::ZERO, true));
+        }
+    }
+}
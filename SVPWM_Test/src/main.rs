@@ -1,7 +1,10 @@
 
-use fixed::types::{I2F14, I6F10};
+use fixed::types::{I16F16, I2F14, I6F10};
 use fixed::types::I4F12;
 mod FOC_func;
+use FOC_func::frames::Dq;
+use FOC_func::modulation::DcBus;
+use FOC_func::Overmodulation;
 use std::io;
 use plotters::prelude::*;
 
@@ -15,7 +18,7 @@ fn main() -> ! {
 
     loop {
         let mut buffer = String::new();
-        println!("Enter command 'speed' or 'angle' or 'plot'");
+        println!("Enter command 'speed' or 'angle' or 'plot' or 'overmodulation'");
         io::stdin().read_line(&mut buffer).unwrap();
         match buffer.as_str() {
             "speed\n" => {
@@ -52,13 +55,19 @@ fn main() -> ! {
                 io::stdin().read_line(&mut buffer).unwrap();
                 let max=buffer.trim().parse::<f32>().unwrap();
                 println!("Max V request: {}",max);
-                let (Valpha,Vbeta)=FOC_func::inverse_park(I6F10::from_num(Vd), I6F10::from_num(Vq), I4F12::from_num(theta));
-                println!("Va={},Vb={}",Valpha,Vbeta);
-                let (i,j,k)=FOC_func::mod_inverse_clarke(Valpha, Vbeta);
-                println!("i={},j={},k={}",i,j,k);
-                let (U,V,W,sector)=FOC_func::svpwm(i,j,k,I6F10::from_num(max));
-                println!("sector: {}",sector);
-                println!("U={},V={},W={}",U,V,W);
+                let Valphabeta=FOC_func::inverse_park(Dq::new(I6F10::from_num(Vd), I6F10::from_num(Vq)), I4F12::from_num(theta));
+                println!("Va={},Vb={}",Valphabeta.alpha,Valphabeta.beta);
+                let Vphase=FOC_func::inverse_clarke(Valphabeta);
+                println!("phase references a={},b={},c={}",Vphase.a,Vphase.b,Vphase.c);
+                let ijk=FOC_func::mod_inverse_clarke(Valphabeta);
+                println!("i={},j={},k={}",ijk.a,ijk.b,ijk.c);
+                let duties=FOC_func::svpwm(ijk,I6F10::from_num(max));
+                println!("sector: {}",duties.sector.number());
+                println!("U={},V={},W={}",duties.u,duties.v,duties.w);
+                // the same reference with Max V as measured bus voltage, duties 0 to 1
+                let modulation=FOC_func::modulation::modulate(Valphabeta,&DcBus::new(I16F16::from_num(max)));
+                println!("duty U={},V={},W={}",modulation.duties.u,modulation.duties.v,modulation.duties.w);
+                println!("modulation index: {} limited: {}",modulation.index,modulation.limited);
             }
             "plot\n" => {
                 println!("Generate plot:");
@@ -115,29 +124,98 @@ fn main() -> ! {
                 let mut UV_v=Vec::new();
                 let mut VW_v=Vec::new();
                 let mut WU_v=Vec::new();
+                // sine PWM phase references centered on max/2, to compare with U V W
+                let mut Va_v=Vec::new();
+                let mut Vb_v=Vec::new();
+                let mut Vc_v=Vec::new();
                 println!("Calculating...");
                 for i in startangle..endangle{
                     let theta=(i as f32)/100.0;
-                    let (Valpha,Vbeta)=FOC_func::inverse_park(I6F10::from_num(Vd), I6F10::from_num(Vq), I4F12::from_num(theta));
-                    let (i,j,k)=FOC_func::mod_inverse_clarke(Valpha, Vbeta);
-                    let (U,V,W,sector)=FOC_func::svpwm(i,j,k,I6F10::from_num(max));
+                    let Valphabeta=FOC_func::inverse_park(Dq::new(I6F10::from_num(Vd), I6F10::from_num(Vq)), I4F12::from_num(theta));
+                    let ijk=FOC_func::mod_inverse_clarke(Valphabeta);
+                    let FOC_func::frames::PhaseDuties{u:U,v:V,w:W,..}=FOC_func::svpwm(ijk,I6F10::from_num(max));
                     U_v.push((theta as f64,f64::from(U)));
                     V_v.push((theta as f64,f64::from(V)));
                     W_v.push((theta as f64,f64::from(W)));
                     UV_v.push((theta as f64,f64::from(U-V)));
                     VW_v.push((theta as f64,f64::from(V-W)));
                     WU_v.push((theta as f64,f64::from(W-U)));
+                    let Vphase=FOC_func::inverse_clarke(Valphabeta);
+                    Va_v.push((theta as f64,f64::from(Vphase.a)+f64::from(max)/2.0));
+                    Vb_v.push((theta as f64,f64::from(Vphase.b)+f64::from(max)/2.0));
+                    Vc_v.push((theta as f64,f64::from(Vphase.c)+f64::from(max)/2.0));
                 }
                 println!("Plotting...");
                 chart.draw_series(LineSeries::new(U_v,&RED)).unwrap();                                    
                 chart.draw_series(LineSeries::new(V_v,&GREEN)).unwrap();  
                 chart.draw_series(LineSeries::new(W_v,&BLUE)).unwrap();  
+                chart.draw_series(LineSeries::new(Va_v,RED.mix(0.3))).unwrap();
+                chart.draw_series(LineSeries::new(Vb_v,GREEN.mix(0.3))).unwrap();
+                chart.draw_series(LineSeries::new(Vc_v,BLUE.mix(0.3))).unwrap();
                 chart_1.draw_series(LineSeries::new(UV_v,&RED)).unwrap();                                    
                 chart_1.draw_series(LineSeries::new(VW_v,&GREEN)).unwrap();  
                 chart_1.draw_series(LineSeries::new(WU_v,&BLUE)).unwrap();
             }
+            "overmodulation\n" => {
+                // U V W of every strategy in overmodulation_plot.png, e.g. modulation
+                // index 1.08 (region 2 of TwoRegion):
+                // printf 'overmodulation\n0\n10.8\n10\n' | cargo run --release
+                println!("Generate overmodulation plot:");
+                buffer="".to_string();
+                println!("Set Vd:");
+                io::stdin().read_line(&mut buffer).unwrap();
+                let Vd=buffer.trim().parse::<f32>().unwrap();
+                println!("Vd request: {}",Vd);
+                println!("Set Vq:");
+                buffer="".to_string();
+                io::stdin().read_line(&mut buffer).unwrap();
+                let Vq=buffer.trim().parse::<f32>().unwrap();
+                println!("Vq request: {}",Vq);
+                println!("Max V:");
+                buffer="".to_string();
+                io::stdin().read_line(&mut buffer).unwrap();
+                let max=buffer.trim().parse::<f32>().unwrap();
+                println!("Max V request: {}",max);
+                let startangle:i32=-314;
+                let endangle:i32=314;
+                // one chart of U V W for every strategy
+                let root_drawing_area = BitMapBackend::new("overmodulation_plot.png", (1024, 1152))
+                .into_drawing_area();
+                root_drawing_area.fill(&WHITE).unwrap();
+                let strategies=[Overmodulation::MinimumPhaseError,Overmodulation::MinimumAmplitudeError,Overmodulation::TwoRegion];
+                println!("Calculating...");
+                for (area,strategy) in root_drawing_area.split_evenly((3,1)).iter().zip(strategies) {
+                    let mut U_v=Vec::new();
+                    let mut V_v=Vec::new();
+                    let mut W_v=Vec::new();
+                    let mut regions=Vec::new();
+                    for i in startangle..endangle{
+                        let theta=(i as f32)/100.0;
+                        let Valphabeta=FOC_func::inverse_park(Dq::new(I6F10::from_num(Vd), I6F10::from_num(Vq)), I4F12::from_num(theta));
+                        let ijk=FOC_func::mod_inverse_clarke(Valphabeta);
+                        let (duties,region)=FOC_func::svpwm_overmodulation(ijk,I6F10::from_num(max),strategy);
+                        U_v.push((theta as f64,f64::from(duties.u)));
+                        V_v.push((theta as f64,f64::from(duties.v)));
+                        W_v.push((theta as f64,f64::from(duties.w)));
+                        if !regions.contains(&region) {
+                            regions.push(region);
+                        }
+                    }
+                    println!("{:?}: {:?}",strategy,regions);
+                    let mut chart = ChartBuilder::on(area)
+                    .caption(format!("U V W {:?} {:?}",strategy,regions), ("sans-serif", 30).into_font())
+                    .margin(10)
+                    .x_label_area_size(20)
+                    .y_label_area_size(40).build_cartesian_2d(-std::f64::consts::PI..std::f64::consts::PI, 0.0..f64::from(max))
+                    .unwrap();
+                    chart.configure_mesh().draw().unwrap();
+                    chart.draw_series(LineSeries::new(U_v,&RED)).unwrap();
+                    chart.draw_series(LineSeries::new(V_v,&GREEN)).unwrap();
+                    chart.draw_series(LineSeries::new(W_v,&BLUE)).unwrap();
+                }
+            }
             _ => {
-                println!("Valid commands: 'speed' or 'angle' or 'plot'");
+                println!("Valid commands: 'speed' or 'angle' or 'plot' or 'overmodulation'");
             }
         }
     }
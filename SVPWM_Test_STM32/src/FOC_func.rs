@@ -1,99 +1,1092 @@
-use fixed::types::I6F10;
+use fixed::consts;
+use fixed::traits::FixedSigned;
 use fixed::types::I4F12;
 mod table_trig;
 mod sin_table;
+pub mod electrical_angle;
+pub mod cordic;
+pub mod frames;
+pub mod per_unit;
+pub mod modulation;
+use frames::{Abc, AlphaBeta, Dq, PhaseDuties, Sector};
 
 /*
 Transforms a couple of vectors in fixed domain Vd and Vq in a pair of vectors in
 rotating domain Valpha Vbeta at an angle theta
-Voltages fixed point I6F10 from -32 to 31.99V resolution 0.00097V
+Voltages fixed point I6F10 from -32 to 31.99V resolution 0.00097V, or any 16 bit
+signed fixed point, e.g. per_unit::PerUnit, the same for all the functions below
+theta is fixed point I4F12 [rad] or an ElectricalAngle
 */
-pub fn inverse_park(Vd:I6F10,Vq:I6F10,theta:I4F12) -> (I6F10, I6F10) {
-let Valpha=Vd * I6F10::from_num(table_trig::cos_t(theta,&sin_table::SIN_TABLE, 12-7)) - Vq * I6F10::from_num(table_trig::sin_t(theta, &sin_table::SIN_TABLE, 12-7));
-let Vbeta=Vd * I6F10::from_num(table_trig::sin_t(theta,&sin_table::SIN_TABLE, 12-7)) + Vq * I6F10::from_num(table_trig::cos_t(theta, &sin_table::SIN_TABLE, 12-7));
-return (Valpha,Vbeta)
+pub fn inverse_park<T:FixedSigned<Bits=i16>>(V:Dq<T>,theta:impl Into<I4F12>) -> AlphaBeta<T> {
+let (sin_theta,cos_theta)=table_trig::sincos_t(theta,&sin_table::TABLE);
+let sin_theta=T::from_num(sin_theta);
+let cos_theta=T::from_num(cos_theta);
+let Valpha=V.d * cos_theta - V.q * sin_theta;
+let Vbeta=V.d * sin_theta + V.q * cos_theta;
+return AlphaBeta::new(Valpha,Vbeta)
+}
+
+/*
+Transforms a couple of vectors in rotating domain Valpha Vbeta in a pair of vectors in
+fixed domain Vd and Vq at an angle theta, the inverse of inverse_park, e.g. for the
+currents Ialpha Ibeta of clarke to Id Iq
+fixed point I6F10 from -32 to 31.99 resolution 0.00097
+theta is fixed point I4F12 [rad] or an ElectricalAngle
+*/
+pub fn park<T:FixedSigned<Bits=i16>>(V:AlphaBeta<T>,theta:impl Into<I4F12>) -> Dq<T> {
+let (sin_theta,cos_theta)=table_trig::sincos_t(theta,&sin_table::TABLE);
+let sin_theta=T::from_num(sin_theta);
+let cos_theta=T::from_num(cos_theta);
+let Vd=V.alpha * cos_theta + V.beta * sin_theta;
+let Vq=-V.alpha * sin_theta + V.beta * cos_theta;
+return Dq::new(Vd,Vq)
 }
 
 /*
 mofified inverse clarke transformation, calculates i j k coefficients useful for SVPWM
 calculation, from Valpha and Vbeta in rotating domain
+returned as Abc with a=i, b=j, c=k
+*/
+pub fn mod_inverse_clarke<T:FixedSigned<Bits=i16>>(V:AlphaBeta<T>) -> Abc<T> {
+    let i=frac_sqrt_3_2::<T>() * V.alpha-V.beta/2;
+    let j=V.beta;
+    let k=-frac_sqrt_3_2::<T>() * V.alpha-V.beta/2;
+    return Abc::new(i,j,k)
+}
+
+/*
+inverse clarke transformation, calculates the phase voltages Va Vb Vc from Valpha and
+Vbeta, amplitude invariant like clarke: Va=Valpha, for sine PWM, simulation and
+plotting of the phase references
+same products of mod_inverse_clarke with alpha and beta swapped:
+mod_inverse_clarke(Valpha,Vbeta) gives (i,j,k)=(Vb,Va,Vc) of inverse_clarke(Vbeta,Valpha)
 */
-pub fn mod_inverse_clarke(Valpha:I6F10,Vbeta:I6F10) -> (I6F10,I6F10,I6F10){
-    let i=I6F10::SQRT_3/2 * Valpha-Vbeta/2;
-    let j=Vbeta;
-    let k=-I6F10::SQRT_3/2 * Valpha-Vbeta/2;
-    return (i,j,k)
+pub fn inverse_clarke<T:FixedSigned<Bits=i16>>(V:AlphaBeta<T>) -> Abc<T> {
+    let Va=V.alpha;
+    let Vb=frac_sqrt_3_2::<T>() * V.beta-V.alpha/2;
+    let Vc=-frac_sqrt_3_2::<T>() * V.beta-V.alpha/2;
+    return Abc::new(Va,Vb,Vc)
 }
 
 /*
 svpwm transforms i j k coefficients to U V W voltage values to be used for setting
 pwm registers for the 3 legs.
+ijk are the i j k coefficients of mod_inverse_clarke
 */
-pub fn svpwm(i:I6F10,j:I6F10,k:I6F10,max:I6F10) -> (I6F10,I6F10,I6F10,u8){
+pub fn svpwm<T:FixedSigned<Bits=i16>>(ijk:Abc<T>,max:T) -> PhaseDuties<T> {
+    let (i,j,k)=(ijk.a,ijk.b,ijk.c);
     let mut N:u8=0;
-    let mut sector:u8;
+    let mut sector:Sector;
     let (T0,T1,T2,U,V,W);
 
-    if i>= I6F10::ZERO {N=N+1;}
-    if j>= I6F10::ZERO {N=N+2;}
-    if k>= I6F10::ZERO {N=N+4;}
+    if i>= T::ZERO {N=N+1;}
+    if j>= T::ZERO {N=N+2;}
+    if k>= T::ZERO {N=N+4;}
     match N {
         1=>{
-            sector=6;
-            T1=I6F10::from_num(-j);
-            T2=I6F10::from_num(-k);
+            sector=Sector::S6;
+            T1=-j;
+            T2=-k;
             T0=max-T1-T2;
             U=T1+T2+T0/2;
             V=T0/2;
             W=T1+T0/2;
         }
         2=>{
-            sector=2;
-            T1=I6F10::from_num(-k);
-            T2=I6F10::from_num(-i);
+            sector=Sector::S2;
+            T1=-k;
+            T2=-i;
             T0=max-T1-T2;
             U=T1+T0/2;
             V=T1+T2+T0/2;
             W=T0/2;
         }
         3=>{
-            sector=1;
-            T1=I6F10::from_num(i);
-            T2=I6F10::from_num(j);
+            sector=Sector::S1;
+            T1=i;
+            T2=j;
             T0=max-T1-T2;
             U=T1+T2+T0/2;
             V=T2+T0/2;
             W=T0/2;
         }
         4=>{
-            sector=4;
-            T1=I6F10::from_num(-i);
-            T2=I6F10::from_num(-j);
+            sector=Sector::S4;
+            T1=-i;
+            T2=-j;
             T0=max-T1-T2;
             U=T0/2;
             V=T1+T0/2;
             W=T1+T2+T0/2;
         }
         5=>{
-            sector=5;
-            T1=I6F10::from_num(k);
-            T2=I6F10::from_num(i);
+            sector=Sector::S5;
+            T1=k;
+            T2=i;
             T0=max-T1-T2;
             U=T2+T0/2;
             V=T0/2;
             W=T1+T2+T0/2;
         }
         6=>{
-            sector=3;
-            T1=I6F10::from_num(j);
-            T2=I6F10::from_num(k);
+            sector=Sector::S3;
+            T1=j;
+            T2=k;
             T0=max-T1-T2;
             U=T0/2;
             V=T1+T2+T0/2;
             W=T2+T0/2;
         }
-        _=>{U=I6F10::ZERO;V=I6F10::ZERO;W=I6F10::ZERO;sector=0;T0=U;T1=V;T2=W;}
+        _=>{U=T::ZERO;V=T::ZERO;W=T::ZERO;sector=Sector::Zero;T0=U;T1=V;T2=W;}
     }
     //println!("T0={} T1={} T2={}",T0,T1,T2);
-    return(U,V,W,sector)
-}
\ No newline at end of file
+    return PhaseDuties{u:U,v:V,w:W,sector}
+}
+
+/*
+overmodulation strategy of svpwm_overmodulation, for a reference vector beyond the
+hexagon of the inverter, where T0=max-T1-T2 of svpwm is negative
+MinimumPhaseError and MinimumAmplitudeError clamp only the vectors beyond the hexagon,
+the fundamental is lower than the reference
+TwoRegion follows the reference with the fundamental of the phase voltages up to
+six-step, from the modulation index 1 of the inscribed circle, |V|=max, to 1.1027 of
+six-step, 2*sqrt(3)/PI
+*/
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Overmodulation {
+    // on the hexagon along the reference, same angle and shorter
+    MinimumPhaseError,
+    // on the nearest point of the hexagon, perpendicular to its side
+    MinimumAmplitudeError,
+    // region 1: the reference is enlarged and clamped as MinimumPhaseError, the
+    // fundamental lost on the hexagon sides is added on the circle arcs
+    // region 2: the vector is held on the vertices of the hexagon for part of the
+    // sector and moves along the side in the rest, six-step at the end
+    TwoRegion,
+}
+
+/*
+region of the vector applied by svpwm_overmodulation
+*/
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ModulationRegion {
+    // the reference as it is, the same of svpwm
+    Linear,
+    // clamped on the hexagon by MinimumPhaseError or MinimumAmplitudeError
+    Clamped,
+    // TwoRegion, modulation index 1 to 1.0491 ((3/PI)*ln(3), circle arcs vanish)
+    Overmodulation1,
+    // TwoRegion, modulation index 1.0491 to 1.1027
+    Overmodulation2,
+    // TwoRegion, modulation index 1.1027 and more, only the hexagon vertices
+    SixStep,
+}
+
+// squared modulation index with 16 fractional bits, the tables are every 2^-7 of it
+const OVERMODULATION_STEP_SHIFT: u32 = 9;
+// ((3/PI)*ln(3))^2 and (2*sqrt(3)/PI)^2, the ends of region 1 and of region 2
+const REGION_1_END_Q16: u32 = 72129;
+const SIX_STEP_Q16: u32 = 79682;
+/*
+the tables are computed in f64 and rounded to 16 fractional bits, the entry i is the
+one of m=sqrt(m2), m2 the start of the region (1 or ((3/PI)*ln(3))^2) plus i*2^-7, the test
+overmodulation_tables recomputes them
+TwoRegion region 1 gain r/m of the reference (16 fractional bits) from m^2=1, the
+enlarged radius r with the fundamental of the clamped trajectory equal to m:
+(3/PI)*(2*ln(sec(d)+tan(d))+r*(PI/3-2*d))=m, d=acos(1/r), solved for r in 1 to
+2/sqrt(3), r=2/sqrt(3) (only the vertices) beyond the end of the region
+*/
+const REGION_1_GAIN: [u32; 14] = [
+    65536, 65571, 65643, 65748, 65886, 66060, 66276, 66539, 66863, 67263, 67771, 68450, 69470, 72102,
+];
+/*
+TwoRegion region 2 hold of the vertices (16 fractional bits) from REGION_1_END_Q16:
+the vector stays on the first vertex while the reference is in the first hold of the
+side, position T2/(T1+T2), on the second vertex in the last one, and moves along the
+side in between, the hold h gives the fundamental m, 0.5 is six-step and beyond:
+(3/PI)*integral for g 0 to PI/3 of 2/sqrt(3)*((1-q)*cos(g)+q*cos(PI/3-g)) = m
+with the reference at g in the sector, its position p=sin(g)/(sin(PI/3-g)+sin(g)),
+and the vector at q=clamp((p-h)/(1-2h),0,1) along the side of the vertices at 0 and PI/3
+*/
+const REGION_2_HOLD: [u32; 16] = [
+    0, 1308, 2642, 4008, 5410, 6855, 8353, 9914, 11554, 13292, 15160, 17204, 19508, 22243, 25909, 32768,
+];
+
+/*
+svpwm with overmodulation, the reference is clamped or modified by the strategy so that
+U V W stay in 0 to max, returns the U V W voltages and the region of the vector
+the same values of svpwm in the linear region, the null vector if max <= 0
+*/
+pub fn svpwm_overmodulation<T:FixedSigned<Bits=i16>>(ijk:Abc<T>,max:T,overmodulation:Overmodulation) -> (PhaseDuties<T>,ModulationRegion) {
+    let (i,j,k)=(ijk.a,ijk.b,ijk.c);
+    let null=PhaseDuties{u:T::ZERO,v:T::ZERO,w:T::ZERO,sector:Sector::Zero};
+    let (sector,T1,T2)=match (i>=T::ZERO,j>=T::ZERO,k>=T::ZERO) {
+        (true,false,false) => (Sector::S6,-j,-k),
+        (false,true,false) => (Sector::S2,-k,-i),
+        (true,true,false) => (Sector::S1,i,j),
+        (false,false,true) => (Sector::S4,-i,-j),
+        (true,false,true) => (Sector::S5,k,i),
+        (false,true,true) => (Sector::S3,j,k),
+        _ => return (null,ModulationRegion::Linear),
+    };
+    if max<=T::ZERO {
+        return (null,ModulationRegion::Linear);
+    }
+    let (t1,t2,m)=(T1.to_bits() as i64,T2.to_bits() as i64,max.to_bits() as i64);
+    let ((t1,t2),region)=match overmodulation {
+        Overmodulation::MinimumPhaseError if t1+t2>m => (clamp_phase(t1,t2,m),ModulationRegion::Clamped),
+        Overmodulation::MinimumAmplitudeError if t1+t2>m => (clamp_amplitude(t1,t2,m),ModulationRegion::Clamped),
+        Overmodulation::TwoRegion => two_region(t1,t2,m),
+        _ => ((t1,t2),ModulationRegion::Linear),
+    };
+    let (T1,T2)=(T::from_bits(t1 as i16),T::from_bits(t2 as i16));
+    // the placement of the active vectors of svpwm_sat
+    let half=(max-T1-T2)/2;
+    let both=T1+T2+half;
+    let first=T1+half;
+    let second=T2+half;
+    let (U,V,W)=match sector {
+        Sector::S1 => (both,second,half),
+        Sector::S2 => (first,both,half),
+        Sector::S3 => (half,both,second),
+        Sector::S4 => (half,first,both),
+        Sector::S5 => (second,half,both),
+        _ => (both,half,first),
+    };
+    (PhaseDuties{u:U,v:V,w:W,sector},region)
+}
+
+/*
+T1 T2 on the hexagon side T1+T2=m along the reference, raw values
+*/
+fn clamp_phase(t1:i64,t2:i64,m:i64) -> (i64,i64) {
+    let t1=(2*t1*m+t1+t2)/(2*(t1+t2));
+    (t1,m-t1)
+}
+
+/*
+T1 T2 on the nearest point of the hexagon, the perpendicular to the side T1+T2=m
+lowers both by the same value, beyond the side it is the vertex, raw values
+*/
+fn clamp_amplitude(t1:i64,t2:i64,m:i64) -> (i64,i64) {
+    let t1=(t1-(t1+t2-m)/2).clamp(0,m);
+    (t1,m-t1)
+}
+
+/*
+T1 T2 of the TwoRegion strategy and its region, raw values, see Overmodulation
+*/
+fn two_region(t1:i64,t2:i64,m:i64) -> ((i64,i64),ModulationRegion) {
+    // m^2=|V|^2/max^2 with |V|^2=4/3*(T1^2+T1*T2+T2^2)
+    let index_2=((4*(t1*t1+t1*t2+t2*t2))<<16)/(3*m*m);
+    if index_2<=1<<16 {
+        ((t1,t2),ModulationRegion::Linear)
+    } else if index_2<=REGION_1_END_Q16 as i64 {
+        let gain=interpolate(&REGION_1_GAIN,index_2 as u32-(1<<16)) as i64;
+        let (t1,t2)=((t1*gain+(1<<15))>>16,(t2*gain+(1<<15))>>16);
+        let t=if t1+t2>m {clamp_phase(t1,t2,m)} else {(t1,t2)};
+        (t,ModulationRegion::Overmodulation1)
+    } else if index_2<SIX_STEP_Q16 as i64 {
+        let hold=interpolate(&REGION_2_HOLD,index_2 as u32-REGION_1_END_Q16) as i64;
+        let position=(t2<<16)/(t1+t2);
+        let position=(((position-hold)<<16)/((1<<16)-2*hold)).clamp(0,1<<16);
+        let t2=(m*position+(1<<15))>>16;
+        ((m-t2,t2),ModulationRegion::Overmodulation2)
+    } else if t2<t1 {
+        ((m,0),ModulationRegion::SixStep)
+    } else {
+        ((0,m),ModulationRegion::SixStep)
+    }
+}
+
+/*
+linear interpolation of a table every 2^OVERMODULATION_STEP_SHIFT, the last value
+beyond its end
+*/
+fn interpolate(table:&[u32],offset:u32) -> u32 {
+    let index=(offset>>OVERMODULATION_STEP_SHIFT) as usize;
+    if index+1>=table.len() {
+        return table[table.len()-1];
+    }
+    let fraction=offset&((1<<OVERMODULATION_STEP_SHIFT)-1);
+    let (a,b)=(table[index],table[index+1]);
+    a+(((b-a)*fraction)>>OVERMODULATION_STEP_SHIFT)
+}
+
+/*
+counting mode of the PWM timer
+Edge: counts up, period of ARR+1 counts, the output is on for compare counts
+Center: counts up and down, period of 2*ARR counts, on for 2*compare counts
+*/
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PwmAlignment {
+    Edge,
+    Center,
+}
+
+/*
+converts the U V W leg voltages of svpwm, 0 to max, to timer compare values:
+voltage/max*(ARR+1) edge aligned, voltage/max*ARR center aligned, rounded to nearest
+and clamped to 0..=arr, ready for the TIM compare registers
+arr is the auto reload register value, with stm32f1xx_hal pwm.get_max_duty()-1
+the sector is the one of duties
+*/
+pub fn duties_to_compare<T:FixedSigned<Bits=i16>>(duties:PhaseDuties<T>,max:T,arr:u16,alignment:PwmAlignment) -> PhaseDuties<u16> {
+    let scale=match alignment {
+        PwmAlignment::Edge => arr as u32+1,
+        PwmAlignment::Center => arr as u32,
+    };
+    let compare=|value:T| -> u16 {
+        if max<=T::ZERO || value<=T::ZERO {
+            0
+        } else if value>=max {
+            arr
+        } else {
+            // value < max < 2^15 and scale <= 2^16, the product fits u32
+            let (value,max)=(value.to_bits() as u32,max.to_bits() as u32);
+            ((value*scale+max/2)/max).min(arr as u32) as u16
+        }
+    };
+    PhaseDuties{u:compare(duties.u),v:compare(duties.v),w:compare(duties.w),sector:duties.sector}
+}
+
+/*
+svpwm with the result in timer compare values, see duties_to_compare
+*/
+pub fn svpwm_compare<T:FixedSigned<Bits=i16>>(ijk:Abc<T>,max:T,arr:u16,alignment:PwmAlignment) -> PhaseDuties<u16> {
+    duties_to_compare(svpwm(ijk,max),max,arr,alignment)
+}
+
+/*
+clarke transformation, transforms the three phase currents Ia Ib Ic in a pair of
+currents Ialpha Ibeta in fixed domain, amplitude invariant: a balanced set of peak I
+gives a vector of length I with Ialpha=Ia
+currents fixed point I6F10 from -32 to 31.99A resolution 0.00097A
+uses all three sensors, the common mode (Ia+Ib+Ic)/3 of the sensor offsets is removed
+the sums of phases are done on the widened raw bits, they can be beyond the value
+type (Ib-Ic of a balanced set reaches sqrt(3) times the peak), the results are
+narrowed once, saturated if the phases are not a balanced set in range
+*/
+pub fn clarke<T:FixedSigned<Bits=i16>>(I:Abc<T>) -> AlphaBeta<T> {
+    let (a,b,c)=widen(I);
+    let Ialpha=narrow::<T>(a-(a+b+c)/3);
+    let Ibeta=mul_frac_1_sqrt_3::<T>(b-c);
+    return AlphaBeta::new(Ialpha,Ibeta)
+}
+
+/*
+clarke transformation with two sensors, Ic is not measured and Ia+Ib+Ic=0 is assumed,
+I.c is not used
+same scaling of clarke
+*/
+pub fn clarke_2<T:FixedSigned<Bits=i16>>(I:Abc<T>) -> AlphaBeta<T> {
+    let (a,b,_)=widen(I);
+    let Ialpha=I.a;
+    let Ibeta=mul_frac_1_sqrt_3::<T>(a+b*2);
+    return AlphaBeta::new(Ialpha,Ibeta)
+}
+
+/*
+clarke transformation with the two phases that can be trusted in the given sector,
+the sector returned by svpwm for the PWM period of the measurement
+with low side shunts the phase with the highest duty has the shortest low side on
+time to sample the current, so it is discarded and rebuilt from Ia+Ib+Ic=0:
+sectors 6 and 1 U, 2 and 3 V, 4 and 5 W
+Sector::Zero (no voltage applied) uses all three sensors like clarke
+widened sums like clarke
+*/
+pub fn clarke_sector<T:FixedSigned<Bits=i16>>(I:Abc<T>,sector:Sector) -> AlphaBeta<T> {
+    let (a,b,c)=widen(I);
+    match sector {
+        Sector::S6 | Sector::S1 => {
+            // Ia=-Ib-Ic
+            let Ialpha=narrow::<T>(-(b+c));
+            let Ibeta=mul_frac_1_sqrt_3::<T>(b-c);
+            AlphaBeta::new(Ialpha,Ibeta)
+        }
+        Sector::S2 | Sector::S3 => {
+            // Ib=-Ia-Ic
+            let Ialpha=I.a;
+            let Ibeta=mul_frac_1_sqrt_3::<T>(-(a+c*2));
+            AlphaBeta::new(Ialpha,Ibeta)
+        }
+        Sector::S4 | Sector::S5 => clarke_2(I),
+        Sector::Zero => clarke(I),
+    }
+}
+
+/*
+counter of the operations that overflowed in the saturating functions (_sat), each
+one is clipped to the range of the value type instead of wrapping or panicking
+one counter can be shared by a whole pipeline, e.g. for a whole PWM period, and
+checked and reset by the caller
+*/
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Saturation {
+    count: u32,
+}
+
+impl Saturation {
+    pub const fn new() -> Saturation {
+        Saturation { count: 0 }
+    }
+
+    /*
+    number of saturated operations since new or reset
+    */
+    pub const fn count(&self) -> u32 {
+        self.count
+    }
+
+    /*
+    true if any operation saturated since new or reset
+    */
+    pub const fn saturated(&self) -> bool {
+        self.count > 0
+    }
+
+    pub fn reset(&mut self) {
+        self.count = 0;
+    }
+
+    fn record<T>(&mut self, checked: Option<T>, saturated: T) -> T {
+        match checked {
+            Some(value) => value,
+            None => {
+                self.count = self.count.saturating_add(1);
+                saturated
+            }
+        }
+    }
+}
+
+/*
+saturating + - * and negation recording the overflows in saturation
+*/
+fn sat_add<T:FixedSigned>(a:T,b:T,saturation:&mut Saturation) -> T {
+    saturation.record(a.checked_add(b),a.saturating_add(b))
+}
+
+fn sat_sub<T:FixedSigned>(a:T,b:T,saturation:&mut Saturation) -> T {
+    saturation.record(a.checked_sub(b),a.saturating_sub(b))
+}
+
+fn sat_mul<T:FixedSigned>(a:T,b:T,saturation:&mut Saturation) -> T {
+    saturation.record(a.checked_mul(b),a.saturating_mul(b))
+}
+
+fn sat_neg<T:FixedSigned>(a:T,saturation:&mut Saturation) -> T {
+    saturation.record(a.checked_neg(),a.saturating_neg())
+}
+
+/*
+inverse_park with saturating arithmetic, a result beyond the range of T is clipped to
+it and counted in saturation, e.g. Vd=Vq=25V in I6F10, same values of inverse_park
+when nothing saturates
+*/
+pub fn inverse_park_sat<T:FixedSigned<Bits=i16>>(V:Dq<T>,theta:impl Into<I4F12>,saturation:&mut Saturation) -> AlphaBeta<T> {
+    let (sin_theta,cos_theta)=table_trig::sincos_t(theta,&sin_table::TABLE);
+    let sin_theta=T::from_num(sin_theta);
+    let cos_theta=T::from_num(cos_theta);
+    // the products overflow only for MIN*-1
+    let Valpha=sat_sub(sat_mul(V.d,cos_theta,saturation),sat_mul(V.q,sin_theta,saturation),saturation);
+    let Vbeta=sat_add(sat_mul(V.d,sin_theta,saturation),sat_mul(V.q,cos_theta,saturation),saturation);
+    AlphaBeta::new(Valpha,Vbeta)
+}
+
+/*
+mod_inverse_clarke with saturating arithmetic, see inverse_park_sat
+*/
+pub fn mod_inverse_clarke_sat<T:FixedSigned<Bits=i16>>(V:AlphaBeta<T>,saturation:&mut Saturation) -> Abc<T> {
+    // same products of mod_inverse_clarke, they are in range as |sqrt(3)/2| < 1,
+    // only the differences can overflow
+    let frac_sqrt_3_2=frac_sqrt_3_2::<T>();
+    let i=sat_sub(sat_mul(frac_sqrt_3_2,V.alpha,saturation),V.beta/2,saturation);
+    let j=V.beta;
+    let k=sat_sub(sat_mul(sat_neg(frac_sqrt_3_2,saturation),V.alpha,saturation),V.beta/2,saturation);
+    Abc::new(i,j,k)
+}
+
+/*
+svpwm with saturating arithmetic, see inverse_park_sat
+the sectors and the U V W formulas are the ones of svpwm
+*/
+pub fn svpwm_sat<T:FixedSigned<Bits=i16>>(ijk:Abc<T>,max:T,saturation:&mut Saturation) -> PhaseDuties<T> {
+    let (i,j,k)=(ijk.a,ijk.b,ijk.c);
+    let s=saturation;
+    // the two active vectors of each sector and where they go: U V W gets
+    // T1+T2, T2 or T1 (first) and 0 on top of T0/2
+    let (sector,T1,T2)=match (i>=T::ZERO,j>=T::ZERO,k>=T::ZERO) {
+        (true,false,false) => (Sector::S6,sat_neg(j,s),sat_neg(k,s)),
+        (false,true,false) => (Sector::S2,sat_neg(k,s),sat_neg(i,s)),
+        (true,true,false) => (Sector::S1,i,j),
+        (false,false,true) => (Sector::S4,sat_neg(i,s),sat_neg(j,s)),
+        (true,false,true) => (Sector::S5,k,i),
+        (false,true,true) => (Sector::S3,j,k),
+        _ => return PhaseDuties{u:T::ZERO,v:T::ZERO,w:T::ZERO,sector:Sector::Zero},
+    };
+    let T0=sat_sub(sat_sub(max,T1,s),T2,s);
+    let half=T0/2;
+    let both=sat_add(sat_add(T1,T2,s),half,s);
+    let first=sat_add(T1,half,s);
+    let second=sat_add(T2,half,s);
+    let (U,V,W)=match sector {
+        Sector::S1 => (both,second,half),
+        Sector::S2 => (first,both,half),
+        Sector::S3 => (half,both,second),
+        Sector::S4 => (half,first,both),
+        Sector::S5 => (second,half,both),
+        _ => (both,half,first),
+    };
+    PhaseDuties{u:U,v:V,w:W,sector}
+}
+
+/*
+sqrt(3)/2 truncated in the value type, for I6F10 the same of I6F10::SQRT_3/2,
+also for types that cannot hold sqrt(3)
+*/
+fn frac_sqrt_3_2<T:FixedSigned>() -> T {
+    T::from_num(consts::SQRT_3/2)
+}
+
+// 1/sqrt(3) with 15 fractional bits, rounded
+const FRAC_1_SQRT_3_Q15: i32 = 18919;
+
+/*
+raw bits of the three phases as i32, sums of up to 3 phases times 2 cannot overflow
+*/
+fn widen<T:FixedSigned<Bits=i16>>(I:Abc<T>) -> (i32,i32,i32) {
+    (I.a.to_bits() as i32,I.b.to_bits() as i32,I.c.to_bits() as i32)
+}
+
+/*
+raw bits back to the value type, saturated to its range
+*/
+fn narrow<T:FixedSigned<Bits=i16>>(bits:i32) -> T {
+    T::from_bits(bits.clamp(i16::MIN as i32,i16::MAX as i32) as i16)
+}
+
+/*
+widened raw bits times 1/sqrt(3) rounded to nearest, narrowed to the value type
+bits up to 3 times the i16 range, the product fits in i32
+*/
+fn mul_frac_1_sqrt_3<T:FixedSigned<Bits=i16>>(bits:i32) -> T {
+    narrow((bits*FRAC_1_SQRT_3_Q15+(1<<14))>>15)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fixed::types::I6F10;
+
+    // I6F10 steps, I6F10::SQRT_3/2 is 0.1% low, 9 steps at 10A
+    const TOLERANCE: f64 = 12.0 / 1024.0;
+
+    /*
+    balanced phase currents of peak amplitude at angle theta [rad]
+    */
+    fn phases(amplitude: f64, theta: f64) -> (f64, f64, f64) {
+        let third = 2.0 * core::f64::consts::PI / 3.0;
+        (amplitude * theta.cos(), amplitude * (theta - third).cos(), amplitude * (theta + third).cos())
+    }
+
+    fn fixed(value: f64) -> I6F10 {
+        I6F10::from_num(value)
+    }
+
+    fn abc((a, b, c): (f64, f64, f64)) -> Abc {
+        Abc::new(fixed(a), fixed(b), fixed(c))
+    }
+
+    fn assert_near(value: I6F10, expected: f64, what: &str) {
+        assert!((value.to_num::<f64>() - expected).abs() <= TOLERANCE, "{} {} expected {}", what, value, expected);
+    }
+
+    #[test]
+    fn park_round_trip() {
+        // the same sin and cos rotate forth and back, so the vector is scaled by
+        // sin^2+cos^2 of the table, plus the I6F10 rounding of the four products
+        let two_pi = (2 * I4F12::PI).to_bits();
+        let vectors = [(10.0, 0.0), (0.0, -10.0), (7.5, 3.25), (-20.0, 12.0), (0.01, -0.02)];
+        for bits in (-two_pi + 1..two_pi).step_by(7) {
+            let theta = I4F12::from_bits(bits);
+            let (sin, cos) = table_trig::sincos_t(theta, &sin_table::TABLE);
+            let (sin, cos) = (I6F10::from_num(sin).to_num::<f64>(), I6F10::from_num(cos).to_num::<f64>());
+            let scale_error = (sin * sin + cos * cos - 1.0).abs();
+            for (alpha, beta) in vectors {
+                let V = AlphaBeta::new(fixed(alpha), fixed(beta));
+                let error = inverse_park(park(V, theta), theta) - V;
+                let tolerance = f64::hypot(alpha, beta) * scale_error + 4.0 / 1024.0;
+                assert!(error.alpha.to_num::<f64>().abs() <= tolerance, "alpha {:?} error {:?} at {}", V, error, theta);
+                assert!(error.beta.to_num::<f64>().abs() <= tolerance, "beta {:?} error {:?} at {}", V, error, theta);
+            }
+        }
+    }
+
+    #[test]
+    fn park_of_clarke() {
+        // balanced currents at the rotor angle are a constant d current, the table step
+        // turns the vector by up to one step of angle
+        let step_rad = (1 << sin_table::DIVISION_SHIFT) as f64 / 4096.0;
+        let tolerance = 10.0 * step_rad + TOLERANCE;
+        for step in 0..360 {
+            let theta = (step as f64).to_radians() - core::f64::consts::PI;
+            let I = park(clarke(abc(phases(10.0, theta))), I4F12::from_num(theta));
+            assert!((I.d.to_num::<f64>() - 10.0).abs() <= tolerance, "Id {} at {}", I.d, theta);
+            assert!(I.q.to_num::<f64>().abs() <= tolerance, "Iq {} at {}", I.q, theta);
+        }
+    }
+
+    #[test]
+    fn inverse_clarke_balanced() {
+        // a vector of length A at theta gives balanced phases of peak A
+        for step in 0..360 {
+            let theta = (step as f64).to_radians();
+            let V = inverse_clarke(AlphaBeta::new(fixed(10.0 * theta.cos()), fixed(10.0 * theta.sin())));
+            let (a, b, c) = phases(10.0, theta);
+            assert_near(V.a, a, "Va");
+            assert_near(V.b, b, "Vb");
+            assert_near(V.c, c, "Vc");
+            assert_near(V.a + V.b + V.c, 0.0, "Va+Vb+Vc");
+        }
+    }
+
+    #[test]
+    fn clarke_of_inverse_clarke() {
+        for (alpha, beta) in [(10.0, 0.0), (0.0, -10.0), (7.5, 3.25), (-12.0, 9.0)] {
+            let V = clarke(inverse_clarke(AlphaBeta::new(fixed(alpha), fixed(beta))));
+            assert_near(V.alpha, alpha, "Valpha");
+            assert_near(V.beta, beta, "Vbeta");
+        }
+    }
+
+    #[test]
+    fn mod_inverse_clarke_scaling() {
+        // mod_inverse_clarke has the same scaling of inverse_clarke, not the 3/2 or sqrt(3)
+        // of other SVPWM formulations: it is inverse_clarke with alpha and beta swapped,
+        // the phases in the order (b, a, c), equal to the last bit
+        let values = [-20.0, -7.3, -1.0, 0.0, 0.001, 2.5, 15.9];
+        for alpha in values {
+            for beta in values {
+                let ijk = mod_inverse_clarke(AlphaBeta::new(fixed(alpha), fixed(beta)));
+                let V = inverse_clarke(AlphaBeta::new(fixed(beta), fixed(alpha)));
+                assert_eq!(ijk, Abc::new(V.b, V.a, V.c), "alpha {} beta {}", alpha, beta);
+            }
+        }
+        // so i, j, k are the phase voltages of a vector mirrored on the alpha=beta line,
+        // their peak is the vector length
+        let ijk = mod_inverse_clarke(AlphaBeta::new(fixed(0.0), fixed(10.0)));
+        assert_eq!(ijk.b, fixed(10.0));
+        assert_near(ijk.a, -5.0, "i");
+        assert_near(ijk.c, -5.0, "k");
+    }
+
+    #[test]
+    fn svpwm_sectors() {
+        // the sector follows the vector angle, S1 from 0 to PI/3 and so on
+        for step in 0..360 {
+            let theta = (step as f64 + 0.5).to_radians();
+            let V = AlphaBeta::new(fixed(5.0 * theta.cos()), fixed(5.0 * theta.sin()));
+            let duties = svpwm(mod_inverse_clarke(V), fixed(24.0));
+            assert_eq!(duties.sector.number() as usize, step / 60 + 1, "sector at {}", step);
+        }
+        assert_eq!(svpwm(Abc::ZERO, fixed(24.0)).sector, Sector::Zero);
+    }
+
+    /*
+    svpwm_overmodulation of the vector of length magnitude at theta [rad] with max 20V
+    */
+    fn overmodulation(magnitude: f64, theta: f64, overmodulation: Overmodulation) -> (PhaseDuties, ModulationRegion) {
+        let V = AlphaBeta::new(fixed(magnitude * theta.cos()), fixed(magnitude * theta.sin()));
+        svpwm_overmodulation(mod_inverse_clarke(V), fixed(20.0), overmodulation)
+    }
+
+    /*
+    alpha beta vector of the phase voltages of U V W, scaled by sqrt(3) to the units of
+    the reference, see modulation
+    */
+    fn applied(duties: PhaseDuties) -> (f64, f64) {
+        let uvw = duties.uvw().map(|v| v.to_num::<f64>());
+        let alpha = (2.0 * uvw.a - uvw.b - uvw.c) / 3.0;
+        let beta = (uvw.b - uvw.c) / 3f64.sqrt();
+        (3f64.sqrt() * alpha, 3f64.sqrt() * beta)
+    }
+
+    /*
+    amplitude of the fundamental of the phase voltage U over one turn of the vector
+    */
+    fn fundamental(magnitude: f64, strategy: Overmodulation) -> f64 {
+        let steps = 3600;
+        let (mut cos_sum, mut sin_sum) = (0.0, 0.0);
+        for step in 0..steps {
+            let theta = 2.0 * core::f64::consts::PI * (step as f64 + 0.5) / steps as f64;
+            let uvw = overmodulation(magnitude, theta, strategy).0.uvw().map(|v| v.to_num::<f64>());
+            let phase = uvw.a - (uvw.a + uvw.b + uvw.c) / 3.0;
+            cos_sum += phase * theta.cos();
+            sin_sum += phase * theta.sin();
+        }
+        2.0 * cos_sum.hypot(sin_sum) / steps as f64
+    }
+
+    const STRATEGIES: [Overmodulation; 3] = [Overmodulation::MinimumPhaseError, Overmodulation::MinimumAmplitudeError, Overmodulation::TwoRegion];
+
+    #[test]
+    fn overmodulation_linear() {
+        // inside the inscribed circle every strategy is svpwm, inside the hexagon the
+        // clamping ones too
+        for step in 0..360 {
+            let theta = (step as f64 + 0.5).to_radians();
+            for (magnitude, strategies) in [(5.0, &STRATEGIES[..]), (19.9, &STRATEGIES[..]), (21.0, &STRATEGIES[..2])] {
+                // 21V is beyond the hexagon side only around the middle of the sectors
+                if magnitude == 21.0 && (theta.to_degrees() % 60.0 - 30.0).abs() < 25.0 {
+                    continue;
+                }
+                let V = AlphaBeta::new(fixed(magnitude * theta.cos()), fixed(magnitude * theta.sin()));
+                for &strategy in strategies {
+                    let (duties, region) = overmodulation(magnitude, theta, strategy);
+                    assert_eq!((duties, region), (svpwm(mod_inverse_clarke(V), fixed(20.0)), ModulationRegion::Linear), "{:?} {} at {}", strategy, magnitude, step);
+                }
+            }
+        }
+        assert_eq!(svpwm_overmodulation(Abc::ZERO, fixed(20.0), Overmodulation::TwoRegion), (svpwm(Abc::ZERO, fixed(20.0)), ModulationRegion::Linear));
+    }
+
+    #[test]
+    fn overmodulation_clamped_on_hexagon() {
+        // 28V is beyond the hexagon of 20V (vertices at 23.09V), the vector is on the
+        // hexagon: one leg at max, one at 0
+        for step in 0..360 {
+            let theta = (step as f64 + 0.5).to_radians();
+            let (phase, phase_region) = overmodulation(28.0, theta, Overmodulation::MinimumPhaseError);
+            let (amplitude, amplitude_region) = overmodulation(28.0, theta, Overmodulation::MinimumAmplitudeError);
+            assert_eq!((phase_region, amplitude_region), (ModulationRegion::Clamped, ModulationRegion::Clamped));
+            for duties in [phase, amplitude] {
+                let uvw = duties.uvw();
+                let (low, high) = (uvw.a.min(uvw.b).min(uvw.c), uvw.a.max(uvw.b).max(uvw.c));
+                assert!(low >= fixed(0.0) && low <= fixed(0.002) && high <= fixed(20.0) && high >= fixed(19.998), "{:?} at {}", duties, step);
+            }
+            // same angle of the reference, and the nearest point is nearer than it
+            let (alpha, beta) = applied(phase);
+            assert!((beta.atan2(alpha) - theta.sin().atan2(theta.cos())).abs() <= 0.003, "{:?} at {}", (alpha, beta), step);
+            let distance = |(alpha, beta): (f64, f64)| f64::hypot(alpha - 28.0 * theta.cos(), beta - 28.0 * theta.sin());
+            assert!(distance(applied(amplitude)) <= distance((alpha, beta)) + 0.01, "{:?} at {}", amplitude, step);
+            assert!(f64::hypot(applied(amplitude).0, applied(amplitude).1) >= f64::hypot(alpha, beta) - 0.01);
+        }
+    }
+
+    #[test]
+    fn two_region_fundamental() {
+        // the fundamental of the phase voltages is the one of the reference, |V|/sqrt(3)
+        // of max 20V, up to the one of six-step 2*max/PI, within the 0.1% of the
+        // I6F10 sqrt(3)/2 of mod_inverse_clarke
+        let six_step = 2.0 * 20.0 / core::f64::consts::PI;
+        for (index, region) in [
+            (0.95, ModulationRegion::Linear),
+            (1.01, ModulationRegion::Overmodulation1),
+            (1.03, ModulationRegion::Overmodulation1),
+            (1.045, ModulationRegion::Overmodulation1),
+            (1.055, ModulationRegion::Overmodulation2),
+            (1.07, ModulationRegion::Overmodulation2),
+            (1.09, ModulationRegion::Overmodulation2),
+            (1.1, ModulationRegion::Overmodulation2),
+            (1.11, ModulationRegion::SixStep),
+            (1.3, ModulationRegion::SixStep),
+        ] {
+            let magnitude = 20.0 * index;
+            let expected = (magnitude / 3f64.sqrt()).min(six_step);
+            let value = fundamental(magnitude, Overmodulation::TwoRegion);
+            assert!((value - expected).abs() <= 0.002 * expected, "m {} fundamental {} expected {}", index, value, expected);
+            assert_eq!(overmodulation(magnitude, 0.3, Overmodulation::TwoRegion).1, region, "m {}", index);
+        }
+        // the clamping strategies lose part of the fundamental
+        assert!(fundamental(22.0, Overmodulation::MinimumPhaseError) < 0.99 * 22.0 / 3f64.sqrt());
+        assert!(fundamental(22.0, Overmodulation::MinimumAmplitudeError) < 22.0 / 3f64.sqrt());
+    }
+
+    /*
+    the TwoRegion tables from their formulas: the radius of region 1 and the hold of
+    region 2 by bisection, the integral of region 2 by Simpson's rule
+    */
+    #[test]
+    fn overmodulation_tables() {
+        use core::f64::consts::PI;
+        let vertex = 2.0 / 3f64.sqrt();
+        let (region_1_end, six_step) = (3.0 / PI * 3f64.ln(), 2.0 * 3f64.sqrt() / PI);
+        assert_eq!(REGION_1_END_Q16, (region_1_end * region_1_end * 65536.0).round() as u32);
+        assert_eq!(SIX_STEP_Q16, (six_step * six_step * 65536.0).round() as u32);
+        // increasing f in lo..hi, x with f(x)=target
+        let bisection = |f: &dyn Fn(f64) -> f64, mut lo: f64, mut hi: f64, target: f64| {
+            for _ in 0..60 {
+                let mid = (lo + hi) / 2.0;
+                if f(mid) < target { lo = mid } else { hi = mid }
+            }
+            (lo + hi) / 2.0
+        };
+        let region_1 = |r: f64| {
+            let d = (1.0 / r).acos();
+            3.0 / PI * (2.0 * (1.0 / d.cos() + d.tan()).ln() + r * (PI / 3.0 - 2.0 * d))
+        };
+        let region_2 = |h: f64| {
+            let n = 2000;
+            let step = PI / 3.0 / n as f64;
+            let sum: f64 = (0..=n).map(|i| {
+                let g = i as f64 * step;
+                let p = g.sin() / ((PI / 3.0 - g).sin() + g.sin());
+                let q = ((p - h) / (1.0 - 2.0 * h)).clamp(0.0, 1.0);
+                let weight = if i == 0 || i == n { 1.0 } else if i % 2 == 1 { 4.0 } else { 2.0 };
+                weight * vertex * ((1.0 - q) * g.cos() + q * (PI / 3.0 - g).cos())
+            }).sum();
+            3.0 / PI * sum * step / 3.0
+        };
+        let m = |start: f64, i: usize| (start + i as f64 / 128.0).sqrt();
+        for (i, gain) in REGION_1_GAIN.iter().enumerate() {
+            let m = m(1.0, i);
+            let r = if m < region_1_end { bisection(&region_1, 1.0, vertex, m) } else { vertex };
+            assert!((*gain as f64 - r / m * 65536.0).abs() <= 0.5 + 1e-3, "gain {} at m {}: {}", i, m, r / m);
+        }
+        for (i, hold) in REGION_2_HOLD.iter().enumerate() {
+            let m = m(region_1_end * region_1_end, i);
+            let h = if m < six_step { bisection(&region_2, 0.0, 0.5, m) } else { 0.5 };
+            assert!((*hold as f64 - h * 65536.0).abs() <= 0.5 + 1e-3, "hold {} at m {}: {}", i, m, h);
+        }
+    }
+
+    #[test]
+    fn six_step() {
+        // only the vertices: every leg at 0 or max
+        for step in 0..360 {
+            let (duties, region) = overmodulation(23.0, (step as f64 + 0.5).to_radians(), Overmodulation::TwoRegion);
+            assert_eq!(region, ModulationRegion::SixStep);
+            let uvw = duties.uvw();
+            assert!([uvw.a, uvw.b, uvw.c].iter().all(|v| *v == fixed(0.0) || *v == fixed(20.0)), "{:?} at {}", duties, step);
+        }
+    }
+
+    // extreme I6F10 values for the saturating functions
+    const EXTREMES: [f64; 9] = [-32.0, -31.999, -25.0, -1.0, 0.0, 1.0, 25.0, 31.0, 31.999];
+
+    #[test]
+    fn inverse_park_saturates() {
+        // Vd=Vq=25V: Valpha or Vbeta reach 35V at PI/4 steps, beyond the I6F10 range,
+        // clipped instead of wrapping
+        let mut saturation = Saturation::new();
+        let V = inverse_park_sat(Dq::new(fixed(25.0), fixed(25.0)), I4F12::FRAC_PI_4, &mut saturation);
+        assert!(saturation.saturated());
+        assert_eq!(V, AlphaBeta::new(fixed(0.0), I6F10::MAX), "{:?}", V);
+        saturation.reset();
+        assert_eq!(saturation.count(), 0);
+        // at every angle the result is the f64 one clipped to the I6F10 range
+        let two_pi = (2 * I4F12::PI).to_bits();
+        for bits in (-two_pi + 1..two_pi).step_by(13) {
+            let theta = I4F12::from_bits(bits);
+            let (sin, cos) = table_trig::sincos_t(theta, &sin_table::TABLE);
+            let (sin, cos) = (I6F10::from_num(sin).to_num::<f64>(), I6F10::from_num(cos).to_num::<f64>());
+            for d in EXTREMES {
+                for q in EXTREMES {
+                    let V = inverse_park_sat(Dq::new(fixed(d), fixed(q)), theta, &mut saturation);
+                    let (alpha, beta) = (d * cos - q * sin, d * sin + q * cos);
+                    let clip = |value: f64| value.clamp(I6F10::MIN.to_num(), I6F10::MAX.to_num());
+                    assert!((V.alpha.to_num::<f64>() - clip(alpha)).abs() <= 4.0 / 1024.0, "alpha {} {} at {}", d, q, theta);
+                    assert!((V.beta.to_num::<f64>() - clip(beta)).abs() <= 4.0 / 1024.0, "beta {} {} at {}", d, q, theta);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn sat_same_as_plain() {
+        // without overflow the saturating functions give the same values and count nothing
+        let mut saturation = Saturation::new();
+        for step in 0..360 {
+            let theta = I4F12::from_num((step as f64).to_radians() - core::f64::consts::PI);
+            for (d, q) in [(2.0, 3.0), (-10.0, 0.5), (0.0, 12.0)] {
+                let Vdq = Dq::new(fixed(d), fixed(q));
+                let V = inverse_park(Vdq, theta);
+                assert_eq!(inverse_park_sat(Vdq, theta, &mut saturation), V);
+                let ijk = mod_inverse_clarke(V);
+                assert_eq!(mod_inverse_clarke_sat(V, &mut saturation), ijk);
+                assert_eq!(svpwm_sat(ijk, fixed(24.0), &mut saturation), svpwm(ijk, fixed(24.0)));
+            }
+        }
+        assert_eq!(saturation.count(), 0);
+    }
+
+    #[test]
+    fn mod_inverse_clarke_sat_extremes() {
+        // the values are the f64 ones, with the truncated sqrt(3)/2, clipped to the
+        // range, counted only when clipped
+        let (min, max) = (I6F10::MIN.to_num::<f64>(), I6F10::MAX.to_num::<f64>());
+        let frac_sqrt_3_2: f64 = frac_sqrt_3_2::<I6F10>().to_num();
+        for alpha in EXTREMES {
+            for beta in EXTREMES {
+                let mut saturation = Saturation::new();
+                let ijk = mod_inverse_clarke_sat(AlphaBeta::new(fixed(alpha), fixed(beta)), &mut saturation);
+                let (i, k) = (frac_sqrt_3_2 * alpha - beta / 2.0, -frac_sqrt_3_2 * alpha - beta / 2.0);
+                assert_near(ijk.a, i.clamp(min, max), "i");
+                assert_eq!(ijk.b, fixed(beta));
+                assert_near(ijk.c, k.clamp(min, max), "k");
+                let clipped = !(min..=max).contains(&i) || !(min..=max).contains(&k);
+                assert_eq!(saturation.saturated(), clipped, "alpha {} beta {}", alpha, beta);
+            }
+        }
+    }
+
+    #[test]
+    fn pipeline_extremes() {
+        // every combination of extreme inputs runs without overflow (debug builds panic
+        // on overflow), the counter reports the saturated ones
+        let mut saturated = 0;
+        let (pi, frac_pi_4) = (I4F12::PI, I4F12::FRAC_PI_4);
+        for theta in [-2 * pi + I4F12::DELTA, -pi, -frac_pi_4, I4F12::ZERO, frac_pi_4, 2 * frac_pi_4, pi, 2 * pi - I4F12::DELTA] {
+            for d in EXTREMES {
+                for q in EXTREMES {
+                    for max in [0.0, 12.0, 31.999] {
+                        let mut saturation = Saturation::new();
+                        let V = inverse_park_sat(Dq::new(fixed(d), fixed(q)), theta, &mut saturation);
+                        svpwm_sat(mod_inverse_clarke_sat(V, &mut saturation), fixed(max), &mut saturation);
+                        if saturation.saturated() {
+                            saturated += 1;
+                        }
+                    }
+                }
+            }
+        }
+        assert!(saturated > 0);
+        // the extreme i j k of svpwm alone
+        let mut saturation = Saturation::new();
+        for i in EXTREMES {
+            for j in EXTREMES {
+                for k in EXTREMES {
+                    svpwm_sat(Abc::new(fixed(i), fixed(j), fixed(k)), I6F10::MAX, &mut saturation);
+                }
+            }
+        }
+        assert!(saturation.saturated());
+        let mut saturation = Saturation::new();
+        svpwm_sat(Abc::new(I6F10::MIN, I6F10::ZERO, I6F10::MIN), I6F10::MAX, &mut saturation);
+        assert!(saturation.saturated(), "-MIN saturates");
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic]
+    fn plain_overflows() {
+        // the plain functions overflow, panic in debug builds, where the _sat ones clip
+        inverse_park(Dq::new(fixed(25.0), fixed(25.0)), I4F12::FRAC_PI_4);
+    }
+
+    #[test]
+    fn compare_values() {
+        let duties = |u: I6F10, v: I6F10, w: I6F10| PhaseDuties { u, v, w, sector: Sector::S1 };
+        let (max, arr) = (fixed(24.0), 999);
+        // half of max: 500 of 1000 counts edge aligned, 499.5 rounded up of 999 center aligned
+        let half = duties(fixed(12.0), fixed(0.0), fixed(24.0));
+        let edge = duties_to_compare(half, max, arr, PwmAlignment::Edge);
+        assert_eq!((edge.u, edge.v, edge.w, edge.sector), (500, 0, 999, Sector::S1));
+        let center = duties_to_compare(half, max, arr, PwmAlignment::Center);
+        assert_eq!((center.u, center.v, center.w), (500, 0, 999));
+        // out of range values are clamped
+        let out = duties_to_compare(duties(fixed(-1.0), fixed(30.0), I6F10::MIN), max, arr, PwmAlignment::Edge);
+        assert_eq!((out.u, out.v, out.w), (0, 999, 0));
+        assert_eq!(duties_to_compare(half, fixed(0.0), arr, PwmAlignment::Edge).uvw(), Abc::new(0, 0, 0));
+        // rounded to nearest over the whole range, for the smallest and largest ARR
+        for arr in [0, 1, 999, 65534, 65535] {
+            for (alignment, scale) in [(PwmAlignment::Edge, arr as f64 + 1.0), (PwmAlignment::Center, arr as f64)] {
+                for bits in (0..=max.to_bits()).step_by(7) {
+                    let value = I6F10::from_bits(bits);
+                    let compare = duties_to_compare(duties(value, value, value), max, arr, alignment).u;
+                    let expected = (bits as f64 / max.to_bits() as f64 * scale + 0.5).floor().min(arr as f64);
+                    assert_eq!(compare as f64, expected, "{} of {} arr {} {:?}", value, max, arr, alignment);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn svpwm_compare_sectors() {
+        // the compare values follow svpwm, the sector is kept
+        for step in 0..36 {
+            let theta = (step as f64 * 10.0 + 5.0).to_radians();
+            let ijk = mod_inverse_clarke(AlphaBeta::new(fixed(8.0 * theta.cos()), fixed(8.0 * theta.sin())));
+            let duties = svpwm(ijk, fixed(24.0));
+            let compare = svpwm_compare(ijk, fixed(24.0), 3599, PwmAlignment::Center);
+            assert_eq!(compare.sector, duties.sector);
+            for (count, value) in [(compare.u, duties.u), (compare.v, duties.v), (compare.w, duties.w)] {
+                assert!((count as f64 - value.to_num::<f64>() / 24.0 * 3599.0).abs() <= 0.5 + 1e-9, "{} {}", count, value);
+            }
+        }
+    }
+
+    #[test]
+    fn clarke_balanced() {
+        // every variant gives (A*cos, A*sin) for balanced currents, in every sector
+        for step in 0..360 {
+            let theta = (step as f64).to_radians();
+            let I = abc(phases(10.0, theta));
+            let mut results = vec![("clarke", clarke(I)), ("clarke_2", clarke_2(I))];
+            for sector in [Sector::Zero, Sector::S1, Sector::S2, Sector::S3, Sector::S4, Sector::S5, Sector::S6] {
+                results.push(("clarke_sector", clarke_sector(I, sector)));
+            }
+            for (name, I) in results {
+                assert_near(I.alpha, 10.0 * theta.cos(), name);
+                assert_near(I.beta, 10.0 * theta.sin(), name);
+            }
+        }
+    }
+
+    #[test]
+    fn clarke_near_full_scale() {
+        // 30A peak, Ib-Ic and Ia+2Ib reach 52A beyond the I6F10 range before 1/sqrt(3)
+        for step in 0..360 {
+            let theta = (step as f64).to_radians();
+            let I = abc(phases(30.0, theta));
+            let mut results = vec![("clarke", clarke(I)), ("clarke_2", clarke_2(I))];
+            for sector in [Sector::Zero, Sector::S1, Sector::S2, Sector::S3, Sector::S4, Sector::S5, Sector::S6] {
+                results.push(("clarke_sector", clarke_sector(I, sector)));
+            }
+            for (name, I) in results {
+                assert_near(I.alpha, 30.0 * theta.cos(), name);
+                assert_near(I.beta, 30.0 * theta.sin(), name);
+            }
+        }
+    }
+
+    #[test]
+    fn clarke_saturates() {
+        // phases in range that are not a balanced set give a vector beyond the range,
+        // it is saturated instead of wrapping around
+        assert_eq!(clarke_2(abc((30.0, 30.0, 0.0))).beta, I6F10::MAX);
+        assert_eq!(clarke_sector(abc((0.0, 30.0, 30.0)), Sector::S1).alpha, I6F10::MIN);
+        assert_eq!(clarke_sector(abc((-30.0, 0.0, -30.0)), Sector::S2).beta, I6F10::MAX);
+        assert_eq!(clarke(abc((30.0, 30.0, -30.0))).beta, I6F10::MAX);
+        assert_eq!(clarke(abc((30.0, -30.0, 30.0))).beta, I6F10::MIN);
+    }
+
+    #[test]
+    fn clarke_common_mode() {
+        // an offset common to the three sensors does not move the vector
+        let I = clarke(abc((2.5, 2.5, 2.5)));
+        assert_near(I.alpha, 0.0, "Ialpha");
+        assert_near(I.beta, 0.0, "Ibeta");
+    }
+
+    #[test]
+    fn clarke_sector_discards_phase() {
+        // a wrong reading of the discarded phase has no effect
+        let I = abc(phases(5.0, 0.3));
+        let wrong = fixed(20.0);
+        let cases = [
+            (Sector::S1, Abc { a: wrong, ..I }),
+            (Sector::S6, Abc { a: wrong, ..I }),
+            (Sector::S2, Abc { b: wrong, ..I }),
+            (Sector::S3, Abc { b: wrong, ..I }),
+            (Sector::S4, Abc { c: wrong, ..I }),
+            (Sector::S5, Abc { c: wrong, ..I }),
+        ];
+        for (sector, I) in cases {
+            let I = clarke_sector(I, sector);
+            assert_near(I.alpha, 5.0 * 0.3f64.cos(), "Ialpha");
+            assert_near(I.beta, 5.0 * 0.3f64.sin(), "Ibeta");
+        }
+    }
+}
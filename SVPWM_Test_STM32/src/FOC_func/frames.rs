@@ -0,0 +1,296 @@
+use core::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+use fixed::traits::FixedSigned;
+use fixed::types::I6F10;
+
+/*
+reference frames of the FOC functions, one struct per frame so a value of one frame
+cannot be passed where another one is expected, e.g. Valpha in place of Vbeta
+the values are voltages or currents, by default fixed point I6F10 from -32 to 31.99
+resolution 0.00097, or per unit values, see per_unit::PerUnit
+*/
+
+/*
+three phase values a b c, e.g. phase currents for clarke or phase voltages of inverse_clarke
+*/
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Abc<T = I6F10> {
+    pub a: T,
+    pub b: T,
+    pub c: T,
+}
+
+/*
+two axes values in fixed domain alpha beta, alpha along phase a
+*/
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AlphaBeta<T = I6F10> {
+    pub alpha: T,
+    pub beta: T,
+}
+
+/*
+two axes values in rotating domain d q, d along the rotor flux
+*/
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Dq<T = I6F10> {
+    pub d: T,
+    pub q: T,
+}
+
+/*
+svpwm output: the U V W leg voltages, 0 to max, and the sector of the vector
+*/
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PhaseDuties<T = I6F10> {
+    pub u: T,
+    pub v: T,
+    pub w: T,
+    pub sector: Sector,
+}
+
+/*
+sector of the voltage vector, S1 starts at alpha and they follow counterclockwise
+every PI/3, Zero is the null vector (no voltage applied)
+*/
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Sector {
+    #[default]
+    Zero,
+    S1,
+    S2,
+    S3,
+    S4,
+    S5,
+    S6,
+}
+
+impl<T: FixedSigned> Abc<T> {
+    pub const ZERO: Abc<T> = Abc::new(T::ZERO, T::ZERO, T::ZERO);
+}
+
+impl<T> Abc<T> {
+    pub const fn new(a: T, b: T, c: T) -> Abc<T> {
+        Abc { a, b, c }
+    }
+
+    /*
+    applies f to every value, e.g. a conversion to or from per unit values
+    */
+    pub fn map<U>(self, f: impl Fn(T) -> U) -> Abc<U> {
+        Abc::new(f(self.a), f(self.b), f(self.c))
+    }
+}
+
+impl<T: FixedSigned> AlphaBeta<T> {
+    pub const ZERO: AlphaBeta<T> = AlphaBeta::new(T::ZERO, T::ZERO);
+}
+
+impl<T> AlphaBeta<T> {
+    pub const fn new(alpha: T, beta: T) -> AlphaBeta<T> {
+        AlphaBeta { alpha, beta }
+    }
+
+    /*
+    applies f to both values
+    */
+    pub fn map<U>(self, f: impl Fn(T) -> U) -> AlphaBeta<U> {
+        AlphaBeta::new(f(self.alpha), f(self.beta))
+    }
+}
+
+impl<T: FixedSigned> Dq<T> {
+    pub const ZERO: Dq<T> = Dq::new(T::ZERO, T::ZERO);
+}
+
+impl<T> Dq<T> {
+    pub const fn new(d: T, q: T) -> Dq<T> {
+        Dq { d, q }
+    }
+
+    /*
+    applies f to both values
+    */
+    pub fn map<U>(self, f: impl Fn(T) -> U) -> Dq<U> {
+        Dq::new(f(self.d), f(self.q))
+    }
+}
+
+impl<T: Copy> PhaseDuties<T> {
+    /*
+    the leg voltages without the sector
+    */
+    pub const fn uvw(&self) -> Abc<T> {
+        Abc::new(self.u, self.v, self.w)
+    }
+}
+
+impl Sector {
+    /*
+    sector number 1 to 6, 0 for Zero
+    */
+    pub const fn number(self) -> u8 {
+        self as u8
+    }
+
+    /*
+    sector of the number 1 to 6, 0 is Zero, None above 6
+    */
+    pub const fn from_number(number: u8) -> Option<Sector> {
+        match number {
+            0 => Some(Sector::Zero),
+            1 => Some(Sector::S1),
+            2 => Some(Sector::S2),
+            3 => Some(Sector::S3),
+            4 => Some(Sector::S4),
+            5 => Some(Sector::S5),
+            6 => Some(Sector::S6),
+            _ => None,
+        }
+    }
+}
+
+impl From<Sector> for u8 {
+    fn from(sector: Sector) -> u8 {
+        sector.number()
+    }
+}
+
+impl TryFrom<u8> for Sector {
+    type Error = u8;
+    fn try_from(number: u8) -> Result<Sector, u8> {
+        Sector::from_number(number).ok_or(number)
+    }
+}
+
+impl<T> From<(T, T, T)> for Abc<T> {
+    fn from((a, b, c): (T, T, T)) -> Abc<T> {
+        Abc::new(a, b, c)
+    }
+}
+
+impl<T> From<Abc<T>> for (T, T, T) {
+    fn from(value: Abc<T>) -> (T, T, T) {
+        (value.a, value.b, value.c)
+    }
+}
+
+impl<T> From<(T, T)> for AlphaBeta<T> {
+    fn from((alpha, beta): (T, T)) -> AlphaBeta<T> {
+        AlphaBeta::new(alpha, beta)
+    }
+}
+
+impl<T> From<AlphaBeta<T>> for (T, T) {
+    fn from(value: AlphaBeta<T>) -> (T, T) {
+        (value.alpha, value.beta)
+    }
+}
+
+impl<T> From<(T, T)> for Dq<T> {
+    fn from((d, q): (T, T)) -> Dq<T> {
+        Dq::new(d, q)
+    }
+}
+
+impl<T> From<Dq<T>> for (T, T) {
+    fn from(value: Dq<T>) -> (T, T) {
+        (value.d, value.q)
+    }
+}
+
+impl<T: Copy> From<PhaseDuties<T>> for Abc<T> {
+    fn from(duties: PhaseDuties<T>) -> Abc<T> {
+        duties.uvw()
+    }
+}
+
+/*
+element wise + - and negation, * and *= by a scalar, for every frame
+*/
+macro_rules! frame_ops {
+    ($frame:ident, $($field:ident),+) => {
+        impl<T: FixedSigned> Add for $frame<T> {
+            type Output = $frame<T>;
+            fn add(self, rhs: $frame<T>) -> $frame<T> {
+                $frame { $($field: self.$field + rhs.$field),+ }
+            }
+        }
+
+        impl<T: FixedSigned> Sub for $frame<T> {
+            type Output = $frame<T>;
+            fn sub(self, rhs: $frame<T>) -> $frame<T> {
+                $frame { $($field: self.$field - rhs.$field),+ }
+            }
+        }
+
+        impl<T: FixedSigned> Neg for $frame<T> {
+            type Output = $frame<T>;
+            fn neg(self) -> $frame<T> {
+                $frame { $($field: -self.$field),+ }
+            }
+        }
+
+        impl<T: FixedSigned> Mul<T> for $frame<T> {
+            type Output = $frame<T>;
+            fn mul(self, rhs: T) -> $frame<T> {
+                $frame { $($field: self.$field * rhs),+ }
+            }
+        }
+
+        impl<T: FixedSigned> AddAssign for $frame<T> {
+            fn add_assign(&mut self, rhs: $frame<T>) {
+                *self = *self + rhs;
+            }
+        }
+
+        impl<T: FixedSigned> SubAssign for $frame<T> {
+            fn sub_assign(&mut self, rhs: $frame<T>) {
+                *self = *self - rhs;
+            }
+        }
+    };
+}
+
+frame_ops!(Abc, a, b, c);
+frame_ops!(AlphaBeta, alpha, beta);
+frame_ops!(Dq, d, q);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixed(value: f64) -> I6F10 {
+        I6F10::from_num(value)
+    }
+
+    #[test]
+    fn ops() {
+        let x = Dq::new(fixed(1.5), fixed(-2.0));
+        let y = Dq::new(fixed(0.25), fixed(4.0));
+        assert_eq!(x + y, Dq::new(fixed(1.75), fixed(2.0)));
+        assert_eq!(x - y, Dq::new(fixed(1.25), fixed(-6.0)));
+        assert_eq!(-x, Dq::new(fixed(-1.5), fixed(2.0)));
+        assert_eq!(x * fixed(2.0), Dq::new(fixed(3.0), fixed(-4.0)));
+        let mut z = x;
+        z += y;
+        z -= y;
+        assert_eq!(z, x);
+        let abc = Abc::new(fixed(1.0), fixed(2.0), fixed(3.0));
+        assert_eq!(abc - abc, Abc::ZERO);
+        assert_eq!(AlphaBeta::new(fixed(1.0), fixed(-1.0)) + -AlphaBeta::new(fixed(1.0), fixed(-1.0)), AlphaBeta::ZERO);
+    }
+
+    #[test]
+    fn conversions() {
+        let (alpha, beta): (I6F10, I6F10) = AlphaBeta::new(fixed(1.0), fixed(2.0)).into();
+        assert_eq!((alpha, beta), (fixed(1.0), fixed(2.0)));
+        assert_eq!(Dq::from((fixed(3.0), fixed(4.0))), Dq::new(fixed(3.0), fixed(4.0)));
+        assert_eq!(Abc::from((fixed(1.0), fixed(2.0), fixed(3.0))), Abc::new(fixed(1.0), fixed(2.0), fixed(3.0)));
+        let duties = PhaseDuties { u: fixed(1.0), v: fixed(2.0), w: fixed(3.0), sector: Sector::S4 };
+        assert_eq!(Abc::from(duties), Abc::new(fixed(1.0), fixed(2.0), fixed(3.0)));
+        for number in 0..=6 {
+            assert_eq!(Sector::try_from(number).map(u8::from), Ok(number));
+        }
+        assert_eq!(Sector::try_from(7), Err(7));
+    }
+}
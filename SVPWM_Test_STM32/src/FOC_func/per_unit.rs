@@ -0,0 +1,167 @@
+use fixed::types::{I16F16, I2F14};
+
+/*
+per unit value, a voltage or current divided by its base value of PerUnitBase
+fixed point I2F14 -2 to 1.99994 resolution 0.00006 of the base, Q15 style with one
+more integer bit: 1 is representable, so with the bus voltage as base the svpwm
+max is 1, and inverse_park has room for the sum of its two products
+the FOC functions work with PerUnit the same as with I6F10 volts, the values only
+depend on the ratio to the base, not on the bus voltage of the board
+*/
+pub type PerUnit = I2F14;
+
+/*
+base values of the per unit system, the physical values at the edges of the pipeline
+are fixed point I16F16 -32768 to 32767.99998 [V] or [A] resolution 0.000015
+e.g. with base voltage = bus voltage Vdc:
+    let base = PerUnitBase::new(Vdc, I_max);
+    let Vdq = Vdq_volts.map(|v| base.volts_to_pu(v));
+    ... inverse_park, mod_inverse_clarke, svpwm(ijk, base.volts_to_pu(Vdc)) ...
+then max is 1 and U V W are the duty cycles 0 to 1 for every bus voltage
+*/
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PerUnitBase {
+    voltage: I16F16,
+    current: I16F16,
+}
+
+impl PerUnitBase {
+    /*
+    panics if a base value is not positive
+    */
+    pub const fn new(voltage: I16F16, current: I16F16) -> PerUnitBase {
+        assert!(voltage.to_bits() > 0 && current.to_bits() > 0, "per unit base values must be positive");
+        PerUnitBase { voltage, current }
+    }
+
+    pub const fn voltage(&self) -> I16F16 {
+        self.voltage
+    }
+
+    pub const fn current(&self) -> I16F16 {
+        self.current
+    }
+
+    /*
+    volts to per unit, saturated to the PerUnit range, +-2 base voltages
+    */
+    pub fn volts_to_pu(&self, volts: I16F16) -> PerUnit {
+        to_pu(volts, self.voltage)
+    }
+
+    /*
+    per unit to volts
+    */
+    pub fn pu_to_volts(&self, value: PerUnit) -> I16F16 {
+        from_pu(value, self.voltage)
+    }
+
+    /*
+    amperes to per unit, saturated to the PerUnit range, +-2 base currents
+    */
+    pub fn amps_to_pu(&self, amps: I16F16) -> PerUnit {
+        to_pu(amps, self.current)
+    }
+
+    /*
+    per unit to amperes
+    */
+    pub fn pu_to_amps(&self, value: PerUnit) -> I16F16 {
+        from_pu(value, self.current)
+    }
+}
+
+fn to_pu(value: I16F16, base: I16F16) -> PerUnit {
+    // I16F16 division keeps 16 fractional bits, more than the 14 of PerUnit
+    PerUnit::saturating_from_num(value.saturating_div(base))
+}
+
+fn from_pu(value: PerUnit, base: I16F16) -> I16F16 {
+    I16F16::from_num(value).saturating_mul(base)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::frames::{Abc, Dq};
+    use super::super::{inverse_park, mod_inverse_clarke, svpwm};
+    use fixed::types::{I4F12, I6F10};
+
+    fn physical(value: f64) -> I16F16 {
+        I16F16::from_num(value)
+    }
+
+    /*
+    per unit pipeline from Vd Vq volts to U V W volts
+    */
+    fn pipeline_pu(base: &PerUnitBase, Vd: f64, Vq: f64, bus: f64, theta: I4F12) -> Abc<f64> {
+        let Vdq = Dq::new(physical(Vd), physical(Vq)).map(|v| base.volts_to_pu(v));
+        let duties = svpwm(mod_inverse_clarke(inverse_park(Vdq, theta)), base.volts_to_pu(physical(bus)));
+        duties.uvw().map(|v| base.pu_to_volts(v).to_num())
+    }
+
+    /*
+    I6F10 pipeline in volts, U V W volts
+    */
+    fn pipeline_volts(Vd: f64, Vq: f64, bus: f64, theta: I4F12) -> Abc<f64> {
+        let Vdq = Dq::new(I6F10::from_num(Vd), I6F10::from_num(Vq));
+        let duties = svpwm(mod_inverse_clarke(inverse_park(Vdq, theta)), I6F10::from_num(bus));
+        duties.uvw().map(|v| v.to_num())
+    }
+
+    fn assert_abc_near(value: Abc<f64>, expected: Abc<f64>, tolerance: f64, what: &str) {
+        let error = [value.a - expected.a, value.b - expected.b, value.c - expected.c];
+        assert!(error.iter().all(|e| e.abs() <= tolerance), "{} {:?} expected {:?}", what, value, expected);
+    }
+
+    #[test]
+    fn conversions() {
+        let base = PerUnitBase::new(physical(48.0), physical(20.0));
+        for volts in [-96.0, -48.0, -12.3, 0.0, 0.001, 24.0, 47.99, 95.99] {
+            let value = base.volts_to_pu(physical(volts));
+            assert!((value.to_num::<f64>() - volts / 48.0).abs() <= PerUnit::DELTA.to_num::<f64>(), "{} V -> {}", volts, value);
+            assert!((base.pu_to_volts(value).to_num::<f64>() - volts).abs() <= 48.0 * PerUnit::DELTA.to_num::<f64>(), "{} V", volts);
+        }
+        assert_eq!(base.amps_to_pu(physical(10.0)), PerUnit::from_num(0.5));
+        assert_eq!(base.pu_to_amps(PerUnit::ONE), physical(20.0));
+        // beyond +-2 base values the per unit value saturates
+        assert_eq!(base.volts_to_pu(physical(200.0)), PerUnit::MAX);
+        assert_eq!(base.volts_to_pu(physical(-200.0)), PerUnit::MIN);
+    }
+
+    #[test]
+    #[should_panic(expected = "must be positive")]
+    fn zero_base_rejected() {
+        PerUnitBase::new(physical(0.0), physical(1.0));
+    }
+
+    #[test]
+    fn same_as_volts() {
+        // on a 24V bus the per unit pipeline gives the leg voltages of the I6F10 one,
+        // within the resolution of the per unit values, 0.0015V
+        let base = PerUnitBase::new(physical(24.0), physical(10.0));
+        for bits in (-12000..12000).step_by(97) {
+            let theta = I4F12::from_bits(bits);
+            let uvw = pipeline_pu(&base, 5.0, 7.5, 24.0, theta);
+            assert_abc_near(uvw, pipeline_volts(5.0, 7.5, 24.0, theta), 0.02, "24V");
+        }
+    }
+
+    #[test]
+    fn bus_independent() {
+        // with the bus voltage as base, the same fraction of the bus gives the same duties
+        // at 12, 48 and 60V, the 48 and 60V buses are beyond the I6F10 range, the leg
+        // voltages are twice the ones of the I6F10 pipeline at half the voltages
+        for bits in (-12000..12000).step_by(97) {
+            let theta = I4F12::from_bits(bits);
+            let duties: Vec<Abc<f64>> = [12.0, 48.0, 60.0].iter().map(|&bus| {
+                let base = PerUnitBase::new(physical(bus), physical(10.0));
+                pipeline_pu(&base, 0.2 * bus, 0.3 * bus, bus, theta).map(|v| v / bus)
+            }).collect();
+            assert_abc_near(duties[1], duties[0], 0.0005, "48V duties");
+            assert_abc_near(duties[2], duties[0], 0.0005, "60V duties");
+            let half = pipeline_volts(0.2 * 30.0, 0.3 * 30.0, 30.0, theta).map(|v| 2.0 * v);
+            assert_abc_near(duties[2].map(|v| v * 60.0), half, 0.05, "60V");
+        }
+    }
+}
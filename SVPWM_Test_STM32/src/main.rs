@@ -17,10 +17,14 @@ use stm32f1xx_hal::{
 };
 
 use core::fmt::Write;
-use fixed::{types::{I2F14, I6F10}, traits::Fixed};
+use fixed::{types::{I16F16, I2F14, I6F10}, traits::Fixed};
 use fixed::types::I4F12;
 use heapless::String;
 mod FOC_func;
+use FOC_func::electrical_angle::ElectricalAngle;
+use FOC_func::frames::Dq;
+use FOC_func::modulation::DcBus;
+use FOC_func::{PwmAlignment, Saturation};
 
 
 #[entry]
@@ -63,11 +67,24 @@ fn main() -> ! {
     // Configure gpio C pin 13 as a push-pull output. The `crh` register is passed to the function
     // in order to configure the port. For pins 0-7, crl should be passed instead.
     let mut led = gpiob.pb13.into_push_pull_output(&mut gpiob.crh);
+    // TIM3 channel 1 2 3 for the U V W legs, edge aligned
+    let c1 = gpioa.pa6.into_alternate_push_pull(&mut gpioa.crl);
+    let c2 = gpioa.pa7.into_alternate_push_pull(&mut gpioa.crl);
+    let c3 = gpiob.pb0.into_alternate_push_pull(&mut gpiob.crl);
+    let pins = (c1, c2, c3);
+    let mut pwm = dp
+        .TIM3
+        .pwm_hz::<Tim3NoRemap, _, _>(pins, &mut afio.mapr, 8.kHz(), &clocks);
+    pwm.enable(Channel::C1);
+    pwm.enable(Channel::C2);
+    pwm.enable(Channel::C3);
+    // get_max_duty is the period ARR+1
+    let arr = pwm.get_max_duty().wrapping_sub(1);
     // Configure the syst timer to trigger an update every second
     let mut timer = Timer::syst(cp.SYST, &clocks).counter_hz();
     timer.start(64.Hz()).unwrap();
 
-    let mut angle: I4F12;
+    let mut angle: ElectricalAngle;
     
     let resolution = 12-7; // 2^-7
     let mut sinval: I2F14;
@@ -95,18 +112,26 @@ fn main() -> ! {
                 writeln!(tx,"Set Max V:");
                 let max=readln_I6F10(&mut rx);
                 writeln!(tx,"Max V request: {}",max);
+                // the angle wraps around every turn, it is not reset between rounds
+                angle = ElectricalAngle::from_radians(I4F12::from_num(-3.14));
+                // saturating versions, an overflow is clipped instead of wrapping
+                let mut saturation=Saturation::new();
                 for j in 1..rounds {
-                    angle = I4F12::from_num(-3.14);
                     for i in startangle..endangle {
-                        let (Valpha,Vbeta)=FOC_func::inverse_park(Vd, Vq, angle);
-//                        writeln!(tx,"Va={},Vb={}",Valpha,Vbeta);
-                        let (i,j,k)=FOC_func::mod_inverse_clarke(Valpha, Vbeta);
-  //                      writeln!(tx,"i={},j={},k={}",i,j,k);
-                        let (U,V,W,sector)=FOC_func::svpwm(i,j,k,max);
+                        let Valphabeta=FOC_func::inverse_park_sat(Dq::new(Vd, Vq), angle, &mut saturation);
+//                        writeln!(tx,"Va={},Vb={}",Valphabeta.alpha,Valphabeta.beta);
+                        let ijk=FOC_func::mod_inverse_clarke_sat(Valphabeta, &mut saturation);
+  //                      writeln!(tx,"i={},j={},k={}",ijk.a,ijk.b,ijk.c);
+                        let duties=FOC_func::svpwm_sat(ijk,max,&mut saturation);
+                        let compare=FOC_func::duties_to_compare(duties,max,arr,PwmAlignment::Edge);
+                        pwm.set_duty(Channel::C1,compare.u);
+                        pwm.set_duty(Channel::C2,compare.v);
+                        pwm.set_duty(Channel::C3,compare.w);
                         angle += speed/64;
                         block!(timer.wait()).unwrap();
                     }
                 }
+                writeln!(tx,"saturated operations: {}",saturation.count());
             }
             b'a' => {
                 writeln!(tx,"Set angle:");
@@ -121,13 +146,21 @@ fn main() -> ! {
                 let max=readln_I6F10(&mut rx);
                 writeln!(tx,"Set Max V:");
                 writeln!(tx,"Max V request: {}",max);
-                let (Valpha,Vbeta)=FOC_func::inverse_park(Vd,Vq,theta);
-                writeln!(tx,"Va={},Vb={}",Valpha,Vbeta);
-                let (i,j,k)=FOC_func::mod_inverse_clarke(Valpha, Vbeta);
-                writeln!(tx,"i={},j={},k={}",i,j,k);
-                let (U,V,W,sector)=FOC_func::svpwm(i,j,k,max);
-                writeln!(tx,"sector: {}",sector);
-                writeln!(tx,"U={},V={},W={}",U,V,W);
+                let mut saturation=Saturation::new();
+                let Valphabeta=FOC_func::inverse_park_sat(Dq::new(Vd,Vq),theta,&mut saturation);
+                writeln!(tx,"Va={},Vb={}",Valphabeta.alpha,Valphabeta.beta);
+                let ijk=FOC_func::mod_inverse_clarke_sat(Valphabeta,&mut saturation);
+                writeln!(tx,"i={},j={},k={}",ijk.a,ijk.b,ijk.c);
+                let duties=FOC_func::svpwm_sat(ijk,max,&mut saturation);
+                writeln!(tx,"saturated operations: {}",saturation.count());
+                writeln!(tx,"sector: {}",duties.sector.number());
+                writeln!(tx,"U={},V={},W={}",duties.u,duties.v,duties.w);
+                let compare=FOC_func::duties_to_compare(duties,max,arr,PwmAlignment::Edge);
+                writeln!(tx,"compare U={},V={},W={} of ARR={}",compare.u,compare.v,compare.w,arr);
+                // the same reference with Max V as measured bus voltage, duties 0 to 1
+                let modulation=FOC_func::modulation::modulate(Valphabeta,&DcBus::new(I16F16::from_num(max)));
+                writeln!(tx,"duty U={},V={},W={}",modulation.duties.u,modulation.duties.v,modulation.duties.w);
+                writeln!(tx,"modulation index: {} limited: {}",modulation.index,modulation.limited);
             }
             _ => {
                 writeln!(tx,"Valid commands: 's'=speed mode or 'a' angle mode");
@@ -1,70 +1,539 @@
+/*
+Sin table generator for table_trig and bam_trig
+
+usage: Sin_Table_Create [options]
+  --start <rad>      first angle of the table, default 0
+  --end <rad>        end angle of the table (included), default the largest angle
+                     table_trig sin_t gives to the table, PI/2 plus the truncation
+                     of the I4F12 constants, or PI/2 with --samples
+  --shift <n>        division_shift 0 to 15, one sample every 2^n steps of the I4F12
+                     angle, default 12-7=5, for --bam one sample every 2^n steps of
+                     the 14 bit quarter wave binary angle, 0 to 14, default 14-8=6
+  --samples <n>      instead of --shift, n samples per quarter wave at any step, the
+                     angle is not a multiple of the I4F12 resolution, so the table
+                     is not usable by table_trig, e.g. for tables indexed by a division
+  --mode <m>         how the points are computed with --shift, left (default), the sin
+                     of the point angle, or error optimised over the angles that use
+                     the point: minimax and rms for sin_t, interp-minimax and
+                     interp-rms for sin_t_interp, see TableMode
+  --type <IxFy>      output fixed point type, signed, 8, 16 or 32 bits, default I2F14
+  --format <f>       rust (const array), c (header), csv or bin (raw little endian)
+  --name <name>      name of the array, default SIN_TABLE or BAM_SIN_TABLE
+  --output <file>    output file, default sin_table.rs/.h/.csv/.bin or bam_sin_table.*
+  --bam              binary angle quarter wave table, 0 to PI/2 included, the range
+                     options are not used
+  --overwrite        replace the output file if it exists
+  --help             print this help
+*/
+
 use std::io::prelude::*;
 use std::fs::OpenOptions;
-use fixed::types::I2F14;
 use fixed::types::I4F12;
 
+const USAGE: &str = "usage: Sin_Table_Create [--start <rad>] [--end <rad>] [--shift <n> | --samples <n>] [--mode <m>] [--type <IxFy>] \
+[--format rust|c|csv|bin] [--name <name>] [--output <file>] [--bam] [--overwrite] [--help]";
+
+/*
+how the table points are computed, same as sin_table::TableMode of the trig crates,
+the bucket of a point is the set of angles that use it
+LeftEdge: sin of the point angle
+NearestMinimax: middle of the sin values of the bucket of sin_t
+NearestRms: mean of the sin values of the bucket of sin_t
+InterpolatedMinimax: sin of the point angle raised by 2/(1+cos(h/2)), h the step [rad]
+InterpolatedRms: sin of the point angle raised to the least RMS error of the chord
+*/
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum TableMode {
+    LeftEdge,
+    NearestMinimax,
+    NearestRms,
+    InterpolatedMinimax,
+    InterpolatedRms,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Format {
+    Rust,
+    C,
+    Csv,
+    Bin,
+}
+
+impl Format {
+    fn extension(self) -> &'static str {
+        match self {
+            Format::Rust => "rs",
+            Format::C => "h",
+            Format::Csv => "csv",
+            Format::Bin => "bin",
+        }
+    }
+}
+
+/*
+signed fixed point type of the table values, int_nbits+frac_nbits is 8, 16 or 32
+*/
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct FixedType {
+    int_nbits: u32,
+    frac_nbits: u32,
+}
+
+impl FixedType {
+    fn nbits(self) -> u32 {
+        self.int_nbits + self.frac_nbits
+    }
+
+    fn name(self) -> String {
+        format!("I{}F{}", self.int_nbits, self.frac_nbits)
+    }
+
+    fn rust_type(self) -> String {
+        format!("i{}", self.nbits())
+    }
+
+    fn c_type(self) -> String {
+        format!("int{}_t", self.nbits())
+    }
+
+    /*
+    raw bits of value rounded to nearest with ties to even like fixed from_num,
+    limited to the maximum and minimum representable values
+    */
+    fn to_bits(self, value: f64) -> i64 {
+        let max = (1i64 << (self.nbits() - 1)) - 1;
+        let bits = (value * (1u64 << self.frac_nbits) as f64).round_ties_even();
+        (bits as i64).clamp(-max - 1, max)
+    }
+
+    fn to_num(self, bits: i64) -> f64 {
+        bits as f64 / (1u64 << self.frac_nbits) as f64
+    }
+}
+
+// largest angle given to the table by table_trig, 2PI - 3/2PI of the truncated I4F12
+// constants, 6435 bits, slightly more than I4F12::FRAC_PI_2
+const MAX_REDUCED_ANGLE: I4F12 = I4F12::from_bits(2 * I4F12::PI.to_bits() - 3 * I4F12::FRAC_PI_2.to_bits());
+
+#[derive(Debug)]
+struct Options {
+    start: f64,
+    end: Option<f64>,
+    division_shift: Option<u16>,
+    samples: Option<u32>,
+    mode: TableMode,
+    fixed_type: FixedType,
+    format: Format,
+    name: Option<String>,
+    output: Option<String>,
+    bam: bool,
+    overwrite: bool,
+}
+
+impl Options {
+    /*
+    end angle of the table [rad], --end or the default one of --samples or --shift tables
+    */
+    fn end(&self) -> f64 {
+        match self.end {
+            Some(end) => end,
+            None if self.samples.is_some() => std::f64::consts::FRAC_PI_2,
+            None => MAX_REDUCED_ANGLE.to_num(),
+        }
+    }
+}
+
 fn main() -> std::io::Result<()>{
-    let sin_table;
+    let options = match parse_args(std::env::args().skip(1)) {
+        Ok(Some(options)) => options,
+        Ok(None) => {println!("{}",USAGE); return Ok(())}
+        Err(error) => {eprintln!("{}\n{}",error,USAGE); std::process::exit(2)}
+    };
+
+    let (sin_table, default_name, description) = if options.bam {
+        // quarter wave sin table indexed by a 16 bit binary angle (65536 = 2PI)
+        // the quadrant takes the top 2 bits, 2^8 steps per quadrant is a shift of 14-8=6
+        let division_shift = options.division_shift.unwrap_or(14-8);
+        let description = format!("binary angle quarter wave sin table, 0 to PI/2 included, division_shift {}", division_shift);
+        (create_bam_table(division_shift, options.fixed_type), "BAM_SIN_TABLE", description)
+    } else if let Some(samples) = options.samples {
+        let end = options.end();
+        let description = format!("sin table from {} to {} [rad], {} samples per quarter wave", options.start, end, samples);
+        (create_table_samples(options.start, end, samples, options.fixed_type), "SIN_TABLE", description)
+    } else {
+        // sin table with a resolution of 2^-7 radians by default
+        // I4F12 type resolution is 2^-12 while I want 2^-7 resolution, so I need to shift right
+        // of 12-7=5 times
+        let division_shift = options.division_shift.unwrap_or(12-7);
+        let start = I4F12::from_num(options.start);
+        let end = I4F12::from_num(options.end());
+        let description = format!("sin table from {} to {} [rad], division_shift {}, {:?}", start, end, division_shift, options.mode);
+        (create_table(start, end, division_shift, options.mode, options.fixed_type), "SIN_TABLE", description)
+    };
+    let description = format!("{}, {}", description, options.fixed_type.name());
+    let name = options.name.clone().unwrap_or(default_name.to_string());
+    let output = options.output.clone().unwrap_or(format!("{}.{}", default_name.to_lowercase(), options.format.extension()));
+
+    // without --overwrite the file must not exist
     let file_res = OpenOptions::new()
-    .append(true)
-    .create_new(true)
-    .open("sin_table.rs");
+    .write(true)
+    .create(options.overwrite)
+    .create_new(!options.overwrite)
+    .truncate(options.overwrite)
+    .open(&output);
     let mut fileh;
 
     match file_res {
         Ok(file_handl) => fileh=file_handl,
-        Err(error) => {println!("{}",error); return Err(error)}
+        Err(error) => {println!("{}: {}",output,error); return Err(error)}
+    }
+
+    match options.format {
+        Format::Rust => write_rust(&mut fileh, &sin_table, &name, &description, options.fixed_type)?,
+        Format::C => write_c(&mut fileh, &sin_table, &name, &description, options.fixed_type)?,
+        Format::Csv => write_csv(&mut fileh, &sin_table, options.fixed_type)?,
+        Format::Bin => write_bin(&mut fileh, &sin_table, options.fixed_type)?,
+    }
+
+    println!("created {} with {} points in {}",description,sin_table.len(),output);
+    Ok(())
+}
+
+/*
+parses the command line options, Ok(None) for --help
+*/
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Option<Options>, String> {
+    let mut options = Options {
+        start: 0.0,
+        end: None,
+        division_shift: None,
+        samples: None,
+        mode: TableMode::LeftEdge,
+        fixed_type: FixedType { int_nbits: 2, frac_nbits: 14 },
+        format: Format::Rust,
+        name: None,
+        output: None,
+        bam: false,
+        overwrite: false,
+    };
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--help" | "-h" => return Ok(None),
+            "--bam" => options.bam = true,
+            "--overwrite" => options.overwrite = true,
+            "--start" | "--end" | "--shift" | "--samples" | "--mode" | "--type" | "--format" | "--name" | "--output" => {
+                let value = args.next().ok_or(format!("missing value for {}", arg))?;
+                match arg.as_str() {
+                    "--start" => options.start = parse_angle(&value)?,
+                    "--end" => options.end = Some(parse_angle(&value)?),
+                    "--shift" => options.division_shift = Some(value.parse().map_err(|_| format!("invalid shift {}", value))?),
+                    "--samples" => options.samples = Some(value.parse().ok().filter(|samples| *samples > 0).ok_or(format!("invalid samples {}", value))?),
+                    "--mode" => options.mode = parse_mode(&value)?,
+                    "--type" => options.fixed_type = parse_type(&value)?,
+                    "--format" => options.format = parse_format(&value)?,
+                    "--name" => options.name = Some(value),
+                    _ => options.output = Some(value),
+                }
+            }
+            _ => return Err(format!("unknown option {}", arg)),
+        }
+    }
+    let max_shift = if options.bam { 14 } else { 15 };
+    if options.division_shift.is_some_and(|shift| shift > max_shift) {
+        return Err(format!("shift must be 0 to {}", max_shift));
+    }
+    if options.division_shift.is_some() && options.samples.is_some() {
+        return Err("use either --shift or --samples".to_string());
     }
+    if options.mode != TableMode::LeftEdge && (options.bam || options.samples.is_some()) {
+        return Err("--mode is only for --shift tables".to_string());
+    }
+    // against the end actually used, the default one too, in I4F12 for --shift tables
+    let empty = if options.samples.is_some() {
+        options.start >= options.end()
+    } else {
+        I4F12::from_num(options.start) >= I4F12::from_num(options.end())
+    };
+    if !options.bam && empty {
+        return Err(format!("start must be less than end {}", options.end()));
+    }
+    Ok(Some(options))
+}
 
-    // create a sin table from 0 to PI with a resolution of 2^-7 radians
-    // I4F12 type resolution is 2^-12 while I want 2^-7 resolution, so I need to shift right 
-    // of 12-7=5 times
-    sin_table=create_table(I4F12::ZERO, I4F12::FRAC_PI_2, 12-7);
+/*
+angle in radians, in the I4F12 range
+*/
+fn parse_angle(value: &str) -> Result<f64, String> {
+    value.parse::<f64>().ok()
+    .filter(|angle| I4F12::checked_from_num(*angle).is_some())
+    .ok_or(format!("invalid angle {}, I4F12 range is -8 to 7.999", value))
+}
 
-    println!("created table with {} points in {:?}",sin_table.len(),fileh);
+/*
+fixed point type name like I2F14 or I1F15, signed with 8, 16 or 32 bits
+*/
+fn parse_type(value: &str) -> Result<FixedType, String> {
+    let invalid = || format!("invalid type {}, expected IxFy with x+y 8, 16 or 32 and x at least 1", value);
+    let (int_nbits, frac_nbits) = value.strip_prefix('I').and_then(|rest| rest.split_once('F')).ok_or_else(invalid)?;
+    let fixed_type = FixedType {
+        int_nbits: int_nbits.parse().map_err(|_| invalid())?,
+        frac_nbits: frac_nbits.parse().map_err(|_| invalid())?,
+    };
+    // at least the sign bit in the integer part
+    if fixed_type.int_nbits < 1 || ![8, 16, 32].contains(&fixed_type.nbits()) {
+        return Err(invalid());
+    }
+    Ok(fixed_type)
+}
+
+fn parse_mode(value: &str) -> Result<TableMode, String> {
+    match value {
+        "left" => Ok(TableMode::LeftEdge),
+        "minimax" => Ok(TableMode::NearestMinimax),
+        "rms" => Ok(TableMode::NearestRms),
+        "interp-minimax" => Ok(TableMode::InterpolatedMinimax),
+        "interp-rms" => Ok(TableMode::InterpolatedRms),
+        _ => Err(format!("invalid mode {}, expected left, minimax, rms, interp-minimax or interp-rms", value)),
+    }
+}
 
-    print!("[");
-    for elements in sin_table.iter(){
-        print!("I2F14::from_num({}),",elements);
+fn parse_format(value: &str) -> Result<Format, String> {
+    match value {
+        "rust" => Ok(Format::Rust),
+        "c" => Ok(Format::C),
+        "csv" => Ok(Format::Csv),
+        "bin" => Ok(Format::Bin),
+        _ => Err(format!("invalid format {}, expected rust, c, csv or bin", value)),
     }
-    print!("]");
+}
 
-    // write the array in file
-    writeln!(fileh,"pub const SIN_TABLE:[i16;{}]=[",sin_table.len())?;
-    for elements in sin_table.iter(){
-        writeln!(fileh,"{}, //I2F14::from_num({})",elements.to_bits(),elements).unwrap();
+/*
+Rust const array of raw bits, each value commented with the number it represents
+*/
+fn write_rust(fileh: &mut impl Write, sin_table: &[(f64, i64)], name: &str, description: &str, fixed_type: FixedType) -> std::io::Result<()> {
+    writeln!(fileh,"// {}",description)?;
+    writeln!(fileh,"pub const {}:[{};{}]=[",name,fixed_type.rust_type(),sin_table.len())?;
+    for (_, elements) in sin_table.iter(){
+        writeln!(fileh,"{}, //{}::from_num({})",elements,fixed_type.name(),fixed_type.to_num(*elements))?;
     }
     writeln!(fileh,"];")?;
+    Ok(())
+}
+
+/*
+C header with the array of raw bits, its length and fractional bits
+*/
+fn write_c(fileh: &mut impl Write, sin_table: &[(f64, i64)], name: &str, description: &str, fixed_type: FixedType) -> std::io::Result<()> {
+    let guard = format!("{}_H", name.to_uppercase());
+    writeln!(fileh,"/* {} */",description)?;
+    writeln!(fileh,"#ifndef {}",guard)?;
+    writeln!(fileh,"#define {}",guard)?;
+    writeln!(fileh)?;
+    writeln!(fileh,"#include <stdint.h>")?;
+    writeln!(fileh)?;
+    writeln!(fileh,"#define {}_LEN {}",name.to_uppercase(),sin_table.len())?;
+    writeln!(fileh,"#define {}_FRAC_NBITS {}",name.to_uppercase(),fixed_type.frac_nbits)?;
+    writeln!(fileh)?;
+    writeln!(fileh,"static const {} {}[{}_LEN] = {{",fixed_type.c_type(),name,name.to_uppercase())?;
+    for (angle_f, elements) in sin_table.iter(){
+        writeln!(fileh,"    {}, /* sin({}) = {} */",elements,angle_f,fixed_type.to_num(*elements))?;
+    }
+    writeln!(fileh,"}};")?;
+    writeln!(fileh)?;
+    writeln!(fileh,"#endif /* {} */",guard)?;
+    Ok(())
+}
 
-Ok(())
+/*
+CSV with index, angle [rad], value and raw bits, one line per point
+*/
+fn write_csv(fileh: &mut impl Write, sin_table: &[(f64, i64)], fixed_type: FixedType) -> std::io::Result<()> {
+    writeln!(fileh,"index,angle,value,bits")?;
+    for (i, (angle_f, elements)) in sin_table.iter().enumerate(){
+        writeln!(fileh,"{},{},{},{}",i,angle_f,fixed_type.to_num(*elements),elements)?;
+    }
+    Ok(())
+}
+
+/*
+raw bits, little endian, 1, 2 or 4 bytes each
+*/
+fn write_bin(fileh: &mut impl Write, sin_table: &[(f64, i64)], fixed_type: FixedType) -> std::io::Result<()> {
+    let nbytes = (fixed_type.nbits() / 8) as usize;
+    for (_, elements) in sin_table.iter(){
+        fileh.write_all(&elements.to_le_bytes()[..nbytes])?;
+    }
+    Ok(())
 }
 
-/* 
-create a fixed point I2F14 sin table -1 to 0.999 res 2^-14
-from start_angle to end_angle, fixed point I4F12, -8 to 7.999, resolution 2^-12, [Rad]
+/*
+create a fixed point sin table, raw bits of fixed_type, e.g. I2F14 -1 to 0.999 res 2^-14
+from start_angle to end_angle included, fixed point I4F12, -8 to 7.999, resolution 2^-12, [Rad]
 division_shift will reduce the resolution and the table size.
 if division_shift=0 the table will have full resolution of 2^-12
 if division_shift=1 the table will have resolution of 2^-11 (half the size)
 if division_shift=2 the table will have resolution of 2^-10 (quarter the size)
 ...
+the last point is the last multiple of the step not beyond end_angle, from 0 to the
+default end angle the table has the length table_trig::SinTable::required_len
+mode sets how each point is computed, see TableMode, the last bucket ends at end_angle
+returns (angle [rad], raw bits) of each point
 */
-fn create_table(start_angle:I4F12, end_angle:I4F12, division_shift:u16)-> Vec<I2F14> {
-    
+fn create_table(start_angle:I4F12, end_angle:I4F12, division_shift:u16, mode:TableMode, fixed_type:FixedType)-> Vec<(f64, i64)> {
+
     let mut sin_table_fixed=Vec::new();
-    let mut angle: I4F12=start_angle; // initialize starting angle
+    // angles as i32 bits, the sum cannot overflow near the end of the I4F12 range
+    let mut angle_bits: i32=start_angle.to_bits() as i32; // initialize starting angle
+    let end_bits: i32=end_angle.to_bits() as i32;
+    let resolution: f64=I4F12::DELTA.to_num();
+    let step: f64=(1<<division_shift) as f64*resolution;
     let mut angle_f: f64;
     let mut sin_value_f: f64;
-    let max_fixed: f64=I2F14::MAX.to_num(); // maximum representable value in fixed point type
 
-    while angle < end_angle {
-        angle_f=I4F12::to_num(angle);
-        sin_value_f=angle_f.sin();
-        if sin_value_f > max_fixed{  // limit to maximum representable value for fixed point type
-            sin_value_f = max_fixed;
+    while angle_bits <= end_bits {
+        angle_f=angle_bits as f64*resolution;
+        // bucket of sin_t, the angles from this point to the next one excluded
+        let bucket=(angle_bits..=(angle_bits+(1<<division_shift)-1).min(end_bits)).map(|bits| (bits as f64*resolution).sin());
+        sin_value_f=match mode {
+            TableMode::LeftEdge => angle_f.sin(),
+            TableMode::NearestMinimax => {
+                let (min, max)=bucket.fold((f64::MAX, f64::MIN), |(min, max), value| (min.min(value), max.max(value)));
+                (min+max)/2.0
             }
-        sin_table_fixed.push(I2F14::from_num(sin_value_f));
-        //println!("angle {:?}",angle);
-        angle=angle + I4F12::DELTA*(2<<(division_shift-1)); // take one sample every 2^division_shift steps
-    }   
+            TableMode::NearestRms => {
+                let (sum, count)=bucket.fold((0.0, 0.0), |(sum, count), value| (sum+value, count+1.0));
+                sum/count
+            }
+            TableMode::InterpolatedMinimax => angle_f.sin()*2.0/(1.0+(step/2.0).cos()),
+            // the chord is about sin*(1-q), q=t*(h-t)/2 along the step, mean q is h^2/12
+            // and mean q^2 is h^4/120, the least squares gain is (1-E[q])/E[(1-q)^2]
+            TableMode::InterpolatedRms => angle_f.sin()*(1.0-step*step/12.0)/(1.0-step*step/6.0+step.powi(4)/120.0),
+        };
+        // limited to maximum representable value for fixed point type
+        sin_table_fixed.push((angle_f, fixed_type.to_bits(sin_value_f)));
+        angle_bits+=1<<division_shift; // take one sample every 2^division_shift steps
+    }
+    sin_table_fixed
+}
+
+/*
+create a fixed point sin table, raw bits of fixed_type, with samples points per quarter
+wave, the step is PI/2/samples [rad] and needs not be a power of two fraction of the
+angle resolution, from start_angle to end_angle included [rad]
+the end point is included when it falls on a step, e.g. PI/2 from 0
+returns (angle [rad], raw bits) of each point
+*/
+fn create_table_samples(start_angle:f64, end_angle:f64, samples:u32, fixed_type:FixedType)-> Vec<(f64, i64)> {
+
+    let step=std::f64::consts::FRAC_PI_2/(samples as f64);
+    // the tolerance keeps the end point when the float division lands just below it
+    let last=((end_angle-start_angle)/step+1e-9).floor() as u32;
+    let mut sin_table_fixed=Vec::new();
+    let mut angle_f: f64;
+    let mut sin_value_f: f64;
+
+    for i in 0..=last {
+        angle_f=start_angle+(i as f64)*step;
+        sin_value_f=angle_f.sin();
+        // limited to maximum representable value for fixed point type
+        sin_table_fixed.push((angle_f, fixed_type.to_bits(sin_value_f)));
+    }
+    sin_table_fixed
+}
+
+/*
+create a fixed point sin table, raw bits of fixed_type, e.g. I2F14 -1 to 0.999 res 2^-14
+for a quarter wave, 0 to PI/2 included, indexed by a 16 bit binary angle where 65536 is 2PI
+the top 2 bits of the angle are the quadrant, the table covers the other 14 bits
+division_shift will reduce the resolution and the table size.
+if division_shift=0 the table will have 2^14+1 points
+if division_shift=1 the table will have 2^13+1 points
+...
+the last point is PI/2, so a mirrored quadrant can read the table backwards
+returns (angle [rad], raw bits) of each point
+*/
+fn create_bam_table(division_shift:u16, fixed_type:FixedType)-> Vec<(f64, i64)> {
+
+    let mut sin_table_fixed=Vec::new();
+    let steps: u32=1<<(14-division_shift); // samples per quarter wave
+    let mut angle_f: f64;
+    let mut sin_value_f: f64;
+
+    for i in 0..=steps {
+        angle_f=(i as f64)*std::f64::consts::FRAC_PI_2/(steps as f64);
+        sin_value_f=angle_f.sin();
+        // limited to maximum representable value for fixed point type
+        sin_table_fixed.push((angle_f, fixed_type.to_bits(sin_value_f)));
+    }
     return sin_table_fixed;
-}
\ No newline at end of file
+}
+
+/*
+copy of the const generator of the trig crates, with the table_trig, bam_trig and
+bam_sin_table it is used by, to check it gives the same tables of this generator
+*/
+#[cfg(test)]
+mod trig {
+    pub mod sin_table;
+    pub mod table_trig;
+    pub mod electrical_angle;
+    pub mod bam_trig;
+    pub mod bam_sin_table;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::trig::sin_table;
+    use super::trig::table_trig::SinTable;
+    use super::trig::bam_sin_table::{BAM_DIVISION_SHIFT, BAM_SIN_TABLE};
+
+    const MODES: [(TableMode, sin_table::TableMode); 5] = [
+        (TableMode::LeftEdge, sin_table::TableMode::LeftEdge),
+        (TableMode::NearestMinimax, sin_table::TableMode::NearestMinimax),
+        (TableMode::NearestRms, sin_table::TableMode::NearestRms),
+        (TableMode::InterpolatedMinimax, sin_table::TableMode::InterpolatedMinimax),
+        (TableMode::InterpolatedRms, sin_table::TableMode::InterpolatedRms),
+    ];
+
+    #[test]
+    fn same_tables_of_const_generator() {
+        // every mode and shift with the default range, for I2F14 and I1F15 that saturates at PI/2
+        for fixed_type in [FixedType { int_nbits: 2, frac_nbits: 14 }, FixedType { int_nbits: 1, frac_nbits: 15 }] {
+            for division_shift in 0..=15 {
+                for (mode, const_mode) in MODES {
+                    let table: Vec<i64> = create_table(I4F12::ZERO, MAX_REDUCED_ANGLE, division_shift, mode, fixed_type).iter().map(|(_, bits)| *bits).collect();
+                    let mut const_table = vec![0; SinTable::required_len(division_shift)];
+                    sin_table::fill_table(&mut const_table, I4F12::FRAC_NBITS, fixed_type.frac_nbits, division_shift, const_mode);
+                    let const_table: Vec<i64> = const_table.iter().map(|bits| *bits as i64).collect();
+                    assert_eq!(table, const_table, "{} shift {} {:?}", fixed_type.name(), division_shift, mode);
+                }
+            }
+        }
+    }
+
+    fn parse(args: &str) -> Result<Option<Options>, String> {
+        parse_args(args.split_whitespace().map(String::from))
+    }
+
+    #[test]
+    fn start_before_end() {
+        // the default end is MAX_REDUCED_ANGLE for --shift tables and PI/2 for --samples
+        assert!(parse("--start 1.5").is_ok());
+        assert_eq!(parse("--start 3").unwrap_err(), format!("start must be less than end {}", MAX_REDUCED_ANGLE.to_num::<f64>()));
+        assert!(parse("--start 1.6 --samples 64").unwrap_err().starts_with("start must be less than end"));
+        assert!(parse("--start 1 --end 0.5").is_err());
+        // equal once rounded to I4F12
+        assert!(parse("--start 1 --end 1.0001").is_err());
+        assert!(parse("--start 1 --end 1.0001 --samples 64").is_ok());
+        // --bam does not use the range
+        assert!(parse("--bam --start 3").is_ok());
+    }
+
+    #[test]
+    fn same_bam_table_of_const_generator() {
+        let table: Vec<i64> = create_bam_table(BAM_DIVISION_SHIFT, FixedType { int_nbits: 2, frac_nbits: 14 }).iter().map(|(_, bits)| *bits).collect();
+        let const_table: Vec<i64> = BAM_SIN_TABLE.iter().map(|bits| *bits as i64).collect();
+        assert_eq!(table, const_table);
+    }
+}
@@ -0,0 +1,32 @@
+use super::bam_trig::BamTable;
+use super::sin_table::{sin_f64, to_fixed_bits};
+use fixed::types::I2F14;
+
+/*
+quarter wave sin table for bam_trig created at compile time, indexed by a 16 bit
+binary angle (65536 = 2PI): the top 2 bits are the quadrant and the table covers
+the other 14 bits with one sample every 2^BAM_DIVISION_SHIFT steps, PI/2 included
+values are the raw bits of the output type I2F14
+BAM_DIVISION_SHIFT=14-8 -> 2^8+1 points
+pass BAM_TABLE to the trig functions, it is BAM_SIN_TABLE checked with its division_shift
+*/
+pub const BAM_DIVISION_SHIFT: u16 = 14-8;
+pub const BAM_SIN_TABLE_LEN: usize = BamTable::required_len(BAM_DIVISION_SHIFT);
+pub const BAM_SIN_TABLE: [i16; BAM_SIN_TABLE_LEN] = create_bam_table(I2F14::FRAC_NBITS);
+pub const BAM_TABLE: BamTable<'static> = BamTable::new(&BAM_SIN_TABLE, BAM_DIVISION_SHIFT);
+
+/*
+create a quarter wave sin table, same algorithm of Sin_Table_Create:
+the point i is the sin of angle i*PI/2/(N-1), rounded to nearest in output_frac_nbits
+fixed point, limited to the i16 maximum
+*/
+pub const fn create_bam_table<const N: usize>(output_frac_nbits: u32) -> [i16; N] {
+    let mut table = [0; N];
+    let mut i = 0;
+    while i < N {
+        let angle_f = i as f64 * core::f64::consts::FRAC_PI_2 / (N - 1) as f64;
+        table[i] = to_fixed_bits(sin_f64(angle_f), output_frac_nbits);
+        i += 1;
+    }
+    table
+}
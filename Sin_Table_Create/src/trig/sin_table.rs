@@ -0,0 +1,149 @@
+use fixed::types::I2F14;
+use fixed::types::I4F12;
+use super::table_trig::{SinTable, MAX_REDUCED_ANGLE};
+
+/*
+sin table created at compile time, used by table_trig sin_t and cos_t
+the table goes from 0 to the largest angle the quadrant decode of table_trig gives,
+PI/2 of the angle type I4F12 plus the truncation of its constants, end point included,
+with one sample every 2^DIVISION_SHIFT angle steps, values are the raw bits of the
+output type I2F14
+to change the table resolution change DIVISION_SHIFT, table size follows:
+DIVISION_SHIFT=12-7 -> resolution 2^-7 rad, 202 points
+DIVISION_SHIFT=12-6 -> resolution 2^-6 rad, 101 points
+DIVISION_SHIFT=0 -> full resolution 2^-12 rad, 6436 points
+TABLE_MODE chooses how the points are computed, see TableMode
+TABLE ties SIN_TABLE to DIVISION_SHIFT, its output type and end angle and is the one
+to pass to the trig functions, it is checked at compile time: a table too short for its
+DIVISION_SHIFT or not of I2F14 samples does not build
+*/
+pub const DIVISION_SHIFT: u16 = 12-7;
+pub const TABLE_MODE: TableMode = TableMode::LeftEdge;
+pub const SIN_TABLE_LEN: usize = SinTable::required_len(DIVISION_SHIFT);
+pub const SIN_TABLE: [i16; SIN_TABLE_LEN] = create_table(I4F12::FRAC_NBITS, I2F14::FRAC_NBITS, DIVISION_SHIFT, TABLE_MODE);
+pub const TABLE: SinTable<'static> = SinTable::new(&SIN_TABLE, DIVISION_SHIFT, I2F14::FRAC_NBITS, MAX_REDUCED_ANGLE);
+
+/*
+how the table points are computed, the angles a point is used for are its bucket:
+for sin_t (nearest) the angles from the point to the next one excluded, for
+sin_t_interp the segments on both sides of the point
+LeftEdge: sin of the point angle, the error of sin_t is one-sided
+NearestMinimax: middle of the sin values of the bucket, halves the max error of sin_t
+NearestRms: mean of the sin values of the bucket, least RMS error of sin_t
+InterpolatedMinimax: sin of the point angle raised by 2/(1+cos(h/2)), h the step [rad],
+  the chord error then goes from -e to +e instead of 0 to 2e, for sin_t_interp
+InterpolatedRms: sin of the point angle raised to the least RMS error of the chord,
+  for sin_t_interp
+*/
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TableMode {
+    LeftEdge,
+    NearestMinimax,
+    NearestRms,
+    InterpolatedMinimax,
+    InterpolatedRms,
+}
+
+/*
+create a sin table, same algorithm of Sin_Table_Create:
+the point i is computed from the sin of angle i*2^division_shift in angle_frac_nbits
+fixed point as set by mode, rounded to nearest in output_frac_nbits fixed point,
+limited to the i16 maximum
+the length N of the array sets the end angle, see SinTable::required_len
+*/
+pub const fn create_table<const N: usize>(angle_frac_nbits: u32, output_frac_nbits: u32, division_shift: u16, mode: TableMode) -> [i16; N] {
+    let mut table = [0; N];
+    fill_table(&mut table, angle_frac_nbits, output_frac_nbits, division_shift, mode);
+    table
+}
+
+/*
+same as create_table on a slice, for tables created at run time, e.g.
+let mut data = vec![0; SinTable::required_len(division_shift)];
+*/
+pub const fn fill_table(table: &mut [i16], angle_frac_nbits: u32, output_frac_nbits: u32, division_shift: u16, mode: TableMode) {
+    let resolution = 1.0 / (1u64 << angle_frac_nbits) as f64;
+    let step = (1u64 << division_shift) as f64 * resolution;
+    // largest reduced angle of the table_trig quadrant decode, 2PI - 3/2PI truncated
+    let max_reduced = 2 * (core::f64::consts::PI / resolution) as u64 - 3 * (core::f64::consts::FRAC_PI_2 / resolution) as u64;
+    let mut i = 0;
+    while i < table.len() {
+        let first = (i as u64) << division_shift;
+        let sin_first = sin_f64(first as f64 * resolution);
+        let value = match mode {
+            TableMode::LeftEdge => sin_first,
+            TableMode::NearestMinimax | TableMode::NearestRms => {
+                let mut last = first + (1u64 << division_shift) - 1;
+                if last > max_reduced {
+                    last = max_reduced;
+                }
+                // min and max, or sum, of the sin values of the bucket, sin has its
+                // maximum at PI/2 that can be inside the last buckets
+                let (mut min, mut max, mut sum) = (sin_first, sin_first, 0.0);
+                let mut angle = first;
+                while angle <= last {
+                    let value = sin_f64(angle as f64 * resolution);
+                    if value < min {
+                        min = value;
+                    }
+                    if value > max {
+                        max = value;
+                    }
+                    sum += value;
+                    angle += 1;
+                }
+                if let TableMode::NearestMinimax = mode {
+                    (min + max) / 2.0
+                } else {
+                    sum / (last - first + 1) as f64
+                }
+            }
+            TableMode::InterpolatedMinimax => {
+                // cos(h/2) = sin(PI/2 - h/2)
+                sin_first * 2.0 / (1.0 + sin_f64(core::f64::consts::FRAC_PI_2 - step / 2.0))
+            }
+            TableMode::InterpolatedRms => {
+                // the chord is about sin*(1-q), q=t*(h-t)/2 along the step, mean q is h^2/12
+                // and mean q^2 is h^4/120, the least squares gain is (1-E[q])/E[(1-q)^2]
+                let h2 = step * step;
+                sin_first * (1.0 - h2 / 12.0) / (1.0 - h2 / 6.0 + h2 * h2 / 120.0)
+            }
+        };
+        table[i] = to_fixed_bits(value, output_frac_nbits);
+        i += 1;
+    }
+}
+
+/*
+sin for 0 to PI/2 with Taylor series, usable in const fn
+the terms are added until they are smaller than the f64 resolution
+*/
+pub const fn sin_f64(angle: f64) -> f64 {
+    let mut term = angle;
+    let mut sum = angle;
+    let mut n = 1.0;
+    while term > 1e-20 || term < -1e-20 {
+        term = -term * angle * angle / ((n + 1.0) * (n + 2.0));
+        sum += term;
+        n += 2.0;
+    }
+    sum
+}
+
+/*
+converts a positive value to the raw bits of a fixed point with frac_nbits fractional
+bits, rounded to nearest with ties to even like fixed from_num, limited to i16 maximum
+*/
+pub const fn to_fixed_bits(value: f64, frac_nbits: u32) -> i16 {
+    let scaled = value * (1u64 << frac_nbits) as f64;
+    let mut bits = scaled as i64;
+    let rest = scaled - bits as f64;
+    if rest > 0.5 || (rest == 0.5 && bits % 2 == 1) {
+        bits += 1;
+    }
+    if bits > i16::MAX as i64 {
+        i16::MAX
+    } else {
+        bits as i16
+    }
+}
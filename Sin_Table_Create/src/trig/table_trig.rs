@@ -0,0 +1,765 @@
+use fixed::consts;
+use fixed::traits::FixedSigned;
+use fixed::types::I2F14;
+use fixed::types::I4F12;
+
+/*
+sin function approximation, using a table fixed point 0 to 0.999,
+angle is fixed point I4F12 -8 to 7.999 res 0.00024 [rad] or an ElectricalAngle
+returns fixed point I2F14 -1 to +0.999
+table is a SinTable, the samples with their division_shift.
+division_shift will reduce the resolution and the table size.
+if division_shift=0 the table will have full resolution of 2^-12
+if division_shift=1 the table will have resolution of 2^-11 (half the size)
+if division_shift=2 the table will have resolution of 2^-10 (quarter the size)
+...
+the value returned is the table sample on the left of the angle, the index is truncated
+not rounded, the error is up to one table step (see sin_t_interp)
+the old sin_t(angle: I4F12, table: &[i16], division_shift: u16) was removed on purpose:
+any slice and shift were accepted, so a short table or a mismatched shift panicked or
+read wrong samples at run time, SinTable checks the pair once when it is built,
+sin_fixed still takes the raw slice and shift for other fixed point types
+*/
+pub fn sin_t(angle: impl Into<I4F12>, table: &SinTable) -> I2F14 {
+    /*
+    angle is converted directly in table index
+    the table size is proportional to angle resolution and maximum value
+    the size is PI/2*resolution
+    the resolution of input angle is I4F12 2^-12, in this case the table size should be
+    1.57*2^12=6434 points, 6436 with the end point, see SinTable::required_len
+    subsampling is needed in order to reduce the table size, the subsampling is
+    determined by resolution parameter
+     */
+    sin_fixed(angle.into(), table.data, table.division_shift)
+}
+
+/*
+cos function approximation, using a table fixed point 0 to 0.999,
+angle is fixed point I4F12 -8 to 7.999 res 0.00024 [rad] or an ElectricalAngle
+returns fixed point I2F14 -1 to +0.999
+table is a SinTable, the samples with their division_shift.
+division_shift will reduce the resolution and the table size.
+if division_shift=0 the table will have full resolution of 2^-12
+if division_shift=1 the table will have resolution of 2^-11 (half the size)
+if division_shift=2 the table will have resolution of 2^-10 (quarter the size)
+...
+the value returned is the table sample on the left of the angle, the index is truncated
+not rounded, the error is up to one table step (see sin_t_interp)
+the old (I4F12, &[i16], u16) signature was removed together with the one of sin_t
+*/
+pub fn cos_t(angle: impl Into<I4F12>, table: &SinTable) -> I2F14 {
+    /*
+    angle is converted directly in table index
+    the table size is proportional to angle resolution and maximum value
+    the size is PI/2*resolution
+    the resolution of input angle is I4F12 2^-12, in this case the table size should be
+    1.57*2^12=6434 points, 6436 with the end point, see SinTable::required_len
+    subsampling is needed in order to reduce the table size, the subsampling is
+    determined by resolution parameter
+     */
+    cos_fixed(angle.into(), table.data, table.division_shift)
+}
+
+/*
+sin function approximation with linear interpolation between adjacent table samples,
+same table and angle range as sin_t.
+the division_shift bits dropped from the angle to build the index are used as the
+fraction of the step between table[index] and table[index+1], so a coarse table
+gives an error close to the full resolution one.
+*/
+pub fn sin_t_interp(angle: impl Into<I4F12>, table: &SinTable) -> I2F14 {
+    sin_fixed_interp(angle.into(), table.data, table.division_shift)
+}
+
+/*
+cos function approximation with linear interpolation between adjacent table samples,
+same table and angle range as cos_t.
+*/
+pub fn cos_t_interp(angle: impl Into<I4F12>, table: &SinTable) -> I2F14 {
+    cos_fixed_interp(angle.into(), table.data, table.division_shift)
+}
+
+/*
+sin and cos function approximation in a single call, returns (sin, cos)
+same table and angle range as sin_t and cos_t, the values are the
+same of the two separate calls but the quadrant of angle is decoded only once
+*/
+pub fn sincos_t(angle: impl Into<I4F12>, table: &SinTable) -> (I2F14, I2F14) {
+    sincos_fixed(angle.into(), table.data, table.division_shift)
+}
+
+/*
+sin and cos function approximation with linear interpolation in a single call,
+returns (sin, cos), same values of sin_t_interp and cos_t_interp
+*/
+pub fn sincos_t_interp(angle: impl Into<I4F12>, table: &SinTable) -> (I2F14, I2F14) {
+    sincos_fixed_interp(angle.into(), table.data, table.division_shift)
+}
+
+/*
+common interface of the sin and cos approximations, implemented by the table
+(TableTrig) and by the other backends, e.g. cordic::Cordic, so they can be swapped
+and compared with the same code
+angle is fixed point I4F12 [rad], returns fixed point I2F14
+*/
+pub trait Trig {
+    fn sin(&self, angle: I4F12) -> I2F14;
+    fn cos(&self, angle: I4F12) -> I2F14;
+    fn sincos(&self, angle: I4F12) -> (I2F14, I2F14);
+}
+
+/*
+table backend: sin_t, cos_t and sincos_t with the given table,
+or the interpolated versions if interpolate is true
+*/
+pub struct TableTrig<'a> {
+    pub table: SinTable<'a>,
+    pub interpolate: bool,
+}
+
+impl Trig for TableTrig<'_> {
+    fn sin(&self, angle: I4F12) -> I2F14 {
+        if self.interpolate {
+            sin_t_interp(angle, &self.table)
+        } else {
+            sin_t(angle, &self.table)
+        }
+    }
+
+    fn cos(&self, angle: I4F12) -> I2F14 {
+        if self.interpolate {
+            cos_t_interp(angle, &self.table)
+        } else {
+            cos_t(angle, &self.table)
+        }
+    }
+
+    fn sincos(&self, angle: I4F12) -> (I2F14, I2F14) {
+        if self.interpolate {
+            sincos_t_interp(angle, &self.table)
+        } else {
+            sincos_t(angle, &self.table)
+        }
+    }
+}
+
+// largest angle given to the table by the quadrant decode, 2PI - 3/2PI of the truncated
+// I4F12 constants, 6435 bits, slightly more than I4F12::FRAC_PI_2
+pub const MAX_REDUCED_ANGLE: I4F12 = I4F12::from_bits(2 * I4F12::PI.to_bits() - 3 * I4F12::FRAC_PI_2.to_bits());
+const MAX_REDUCED_ANGLE_BITS: u32 = MAX_REDUCED_ANGLE.to_bits() as u32;
+
+/*
+sin table samples together with how they were created: samples of sin from angle 0
+to end_angle included, one every 2^division_shift steps of the I4F12 angle, raw bits
+of a fixed point with output_frac_nbits fractional bits
+SinTable::new checks once that the samples are I2F14, the output of sin_t, and that
+they reach the largest reduced angle, so every index sin_t, cos_t and the interpolated
+versions compute is in range and the lookup cannot panic, in a const
+(e.g. sin_table::TABLE) the check is done at compile time
+*/
+#[derive(Clone, Copy, Debug)]
+pub struct SinTable<'a> {
+    data: &'a [i16],
+    division_shift: u16,
+    output_frac_nbits: u32,
+    end_angle: I4F12,
+}
+
+impl<'a> SinTable<'a> {
+    /*
+    panics if division_shift is 16 or more, if the samples are not I2F14, if end_angle
+    is below MAX_REDUCED_ANGLE, if data is shorter than required_len(division_shift)
+    or if its length is not the one of the samples from 0 to end_angle
+    */
+    pub const fn new(data: &'a [i16], division_shift: u16, output_frac_nbits: u32, end_angle: I4F12) -> Self {
+        assert!(division_shift < 16, "division_shift must be below 16");
+        assert!(output_frac_nbits == I2F14::FRAC_NBITS, "sin table samples must be I2F14");
+        assert!(end_angle.to_bits() >= MAX_REDUCED_ANGLE.to_bits(), "sin table end angle below the largest reduced angle");
+        assert!(data.len() >= Self::required_len(division_shift), "sin table too short for its division_shift");
+        assert!(data.len() == (end_angle.to_bits() >> division_shift) as usize + 1, "sin table length does not match its end angle");
+        SinTable { data, division_shift, output_frac_nbits, end_angle }
+    }
+
+    /*
+    minimum number of samples for division_shift, the last one is the sample of
+    the largest reduced angle
+    */
+    pub const fn required_len(division_shift: u16) -> usize {
+        (MAX_REDUCED_ANGLE_BITS >> division_shift) as usize + 1
+    }
+
+    pub const fn data(&self) -> &'a [i16] {
+        self.data
+    }
+
+    pub const fn division_shift(&self) -> u16 {
+        self.division_shift
+    }
+
+    pub const fn output_frac_nbits(&self) -> u32 {
+        self.output_frac_nbits
+    }
+
+    pub const fn end_angle(&self) -> I4F12 {
+        self.end_angle
+    }
+}
+
+/*
+generic versions of the table functions, the I4F12 and I2F14 functions above are
+thin wrappers of these ones
+angle is any signed fixed point type able to hold -2PI to 2PI [rad] (at least 4 integer
+bits), e.g. I4F12 or I8F24
+the return value is any signed fixed point type, e.g. I2F14 or I1F15, the table stores
+the raw bits of the same type, so for I1F15 the table is &[i16] with 2^-15 resolution
+division_shift is in units of the angle resolution: the table resolution is
+2^-(angle fractional bits - division_shift), for an I8F24 angle and a 2^-7 table
+division_shift is 24-7
+if the return type cannot represent 1 (e.g. I1F15) the maximum value is used instead
+the table length is not checked like SinTable does, a table shorter than the largest
+reduced angle >> division_shift + 1 panics
+*/
+pub fn sin_fixed<A: FixedSigned, O: FixedSigned>(angle: A, table: &[O::Bits], division_shift: u16) -> O {
+    match sin_quadrant(angle) {
+        Some((angle_int, sign)) => apply_sign(lookup_left::<A, O>(angle_int, table, division_shift), sign),
+        None => O::ZERO, // beyond +-2PI overflow management
+    }
+}
+
+pub fn cos_fixed<A: FixedSigned, O: FixedSigned>(angle: A, table: &[O::Bits], division_shift: u16) -> O {
+    match cos_quadrant(angle) {
+        Some((angle_int, sign)) => apply_sign(lookup_left::<A, O>(angle_int, table, division_shift), sign),
+        None => one::<O>(), // beyond +-2PI overflow management
+    }
+}
+
+pub fn sin_fixed_interp<A: FixedSigned, O: FixedSigned>(angle: A, table: &[O::Bits], division_shift: u16) -> O {
+    match sin_quadrant(angle) {
+        Some((angle_int, sign)) => apply_sign(lookup_linear::<A, O>(angle_int, table, division_shift), sign),
+        None => O::ZERO, // beyond +-2PI overflow management
+    }
+}
+
+pub fn cos_fixed_interp<A: FixedSigned, O: FixedSigned>(angle: A, table: &[O::Bits], division_shift: u16) -> O {
+    match cos_quadrant(angle) {
+        Some((angle_int, sign)) => apply_sign(lookup_linear::<A, O>(angle_int, table, division_shift), sign),
+        None => one::<O>(), // beyond +-2PI overflow management
+    }
+}
+
+pub fn sincos_fixed<A: FixedSigned, O: FixedSigned>(angle: A, table: &[O::Bits], division_shift: u16) -> (O, O) {
+    match sincos_quadrant(angle) {
+        Some((sin_int, sin_sign, cos_int, cos_sign)) => (
+            apply_sign(lookup_left::<A, O>(sin_int, table, division_shift), sin_sign),
+            apply_sign(lookup_left::<A, O>(cos_int, table, division_shift), cos_sign),
+        ),
+        None => (O::ZERO, one::<O>()), // beyond +-2PI overflow management
+    }
+}
+
+pub fn sincos_fixed_interp<A: FixedSigned, O: FixedSigned>(angle: A, table: &[O::Bits], division_shift: u16) -> (O, O) {
+    match sincos_quadrant(angle) {
+        Some((sin_int, sin_sign, cos_int, cos_sign)) => (
+            apply_sign(lookup_linear::<A, O>(sin_int, table, division_shift), sin_sign),
+            apply_sign(lookup_linear::<A, O>(cos_int, table, division_shift), cos_sign),
+        ),
+        None => (O::ZERO, one::<O>()), // beyond +-2PI overflow management
+    }
+}
+
+/*
+quadrant limits PI/2, PI, 3/2PI, 2PI in the angle type
+truncated like the constants of the fixed types, so for I4F12 they are the same
+of I4F12::FRAC_PI_2, I4F12::PI, 3*I4F12::FRAC_PI_2 and 2*I4F12::PI
+*/
+fn quadrant_limits<A: FixedSigned>() -> (A, A, A, A) {
+    let frac_pi_2 = A::from_num(consts::FRAC_PI_2);
+    let pi = A::from_num(consts::PI);
+    (frac_pi_2, pi, frac_pi_2 + frac_pi_2 + frac_pi_2, pi + pi)
+}
+
+/*
+maps angle to the 0 to PI/2 interval of the table for the sin function,
+returns the reduced angle and the sign to be applied to the table value,
+None if angle is outside -2PI to 2PI
+*/
+pub(crate) fn sin_quadrant<A: FixedSigned>(angle: A) -> Option<(A, bool)> {
+    let (frac_pi_2, pi, three_frac_pi_2, two_pi) = quadrant_limits::<A>();
+    if angle <= -two_pi
+    {
+        // less than -2PI & overflow management
+        None
+    } else if angle < -three_frac_pi_2
+    {
+        // -2PI to -3/2PI -> 0 to PI/2
+        Some((angle + two_pi, true))
+    } else if angle < -pi
+    {
+        // -3/2PI to -PI -> PI/2 to 0
+        Some((-pi - angle, true))
+    } else if angle < -frac_pi_2
+    {
+        // -PI to -PI/2 -> 0 to PI/2
+        Some((angle + pi, false))
+    } else if angle < A::ZERO
+    {
+        // -PI/2 to 0 -> PI/2 to 0
+        Some((-angle, false))
+    } else if angle < frac_pi_2
+    {
+        // 0 to PI/2 -> 0 to PI/2
+        Some((angle, true))
+    } else if angle < pi
+    {
+        // PI/2 to PI -> PI/2 to 0
+        Some((pi - angle, true))
+    } else if angle < three_frac_pi_2
+    {
+        // PI to 3/2PI -> 0 to PI/2
+        Some((angle - pi, false))
+    } else if angle < two_pi
+    {
+        // 3/2PI to 2PI -> PI/2 to 0
+        Some((two_pi - angle, false))
+    } else {
+        // > 2PI overflow management
+        None
+    }
+}
+
+/*
+maps angle to the 0 to PI/2 interval of the table for the cos function,
+returns the reduced angle and the sign to be applied to the table value,
+None if angle is outside -2PI to 2PI
+*/
+pub(crate) fn cos_quadrant<A: FixedSigned>(angle: A) -> Option<(A, bool)> {
+    let (frac_pi_2, pi, three_frac_pi_2, two_pi) = quadrant_limits::<A>();
+    if angle <= -two_pi
+    {
+        // less than -2PI & overflow management
+        None
+    } else if angle < -three_frac_pi_2
+    {
+        // -2PI to -3/2PI -> PI/2 to 0
+        Some((-three_frac_pi_2 - angle, true))
+    } else if angle < -pi
+    {
+        // -3/2PI to -PI -> 0 to PI/2
+        Some((three_frac_pi_2 + angle, false))
+    } else if angle < -frac_pi_2
+    {
+        // -PI to -PI/2 -> PI/2 to 0
+        Some((-frac_pi_2 - angle, false))
+    } else if angle < A::ZERO
+    {
+        // -PI/2 to 0 -> 0 to PI/2
+        Some((angle + frac_pi_2, true))
+    } else if angle < frac_pi_2
+    {
+        // 0 to PI/2 -> PI/2 to 0
+        Some((frac_pi_2 - angle, true))
+    } else if angle < pi
+    {
+        // PI/2 to PI -> 0 to PI/2
+        Some((angle -frac_pi_2, false))
+    } else if angle < three_frac_pi_2
+    {
+        // PI to 3/2PI -> PI/2 to 0
+        Some((three_frac_pi_2 - angle, false))
+    } else if angle < two_pi
+    {
+        // 3/2PI to 2PI -> 0 to PI/2
+        Some((angle -three_frac_pi_2, true))
+    } else {
+        // > 2PI overflow management
+        None
+    }
+}
+
+/*
+maps angle to the 0 to PI/2 interval of the table for both sin and cos functions,
+returns (sin reduced angle, sin sign, cos reduced angle, cos sign),
+None if angle is outside -2PI to 2PI
+the reduced angles are the same of sin_quadrant and cos_quadrant
+*/
+pub(crate) fn sincos_quadrant<A: FixedSigned>(angle: A) -> Option<(A, bool, A, bool)> {
+    let (frac_pi_2, pi, three_frac_pi_2, two_pi) = quadrant_limits::<A>();
+    if angle <= -two_pi
+    {
+        // less than -2PI & overflow management
+        None
+    } else if angle < -three_frac_pi_2
+    {
+        // -2PI to -3/2PI
+        Some((angle + two_pi, true, -three_frac_pi_2 - angle, true))
+    } else if angle < -pi
+    {
+        // -3/2PI to -PI
+        Some((-pi - angle, true, three_frac_pi_2 + angle, false))
+    } else if angle < -frac_pi_2
+    {
+        // -PI to -PI/2
+        Some((angle + pi, false, -frac_pi_2 - angle, false))
+    } else if angle < A::ZERO
+    {
+        // -PI/2 to 0
+        Some((-angle, false, angle + frac_pi_2, true))
+    } else if angle < frac_pi_2
+    {
+        // 0 to PI/2
+        Some((angle, true, frac_pi_2 - angle, true))
+    } else if angle < pi
+    {
+        // PI/2 to PI
+        Some((pi - angle, true, angle -frac_pi_2, false))
+    } else if angle < three_frac_pi_2
+    {
+        // PI to 3/2PI
+        Some((angle - pi, false, three_frac_pi_2 - angle, false))
+    } else if angle < two_pi
+    {
+        // 3/2PI to 2PI
+        Some((two_pi - angle, false, angle -three_frac_pi_2, true))
+    } else {
+        // > 2PI overflow management
+        None
+    }
+}
+
+/*
+table sample on the left of angle_int, angle_int in 0 to PI/2, the bits below
+division_shift are dropped (truncated, not rounded to the nearest sample)
+*/
+fn lookup_left<A: FixedSigned, O: FixedSigned>(angle_int: A, table: &[O::Bits], division_shift: u16) -> O {
+    let index: usize = (angle_int.to_bits() >> division_shift as u32).try_into().unwrap_or(0);
+    O::from_bits(table[index])
+}
+
+/*
+linear interpolation between the two table samples around angle_int, angle_int in 0 to PI/2
+the bits below division_shift are the fraction of the step, the last table sample
+is held when angle_int falls past it
+*/
+fn lookup_linear<A: FixedSigned, O: FixedSigned>(angle_int: A, table: &[O::Bits], division_shift: u16) -> O {
+    let shift = division_shift as u32;
+    let bits = angle_int.to_bits();
+    let index_bits = bits >> shift;
+    let index: usize = index_bits.try_into().unwrap_or(0);
+    let frac: i64 = (bits - (index_bits << shift)).try_into().unwrap_or(0);
+    let left: i64 = table[index].try_into().unwrap_or(0);
+    let right: i64 = if index + 1 < table.len() { table[index + 1].try_into().unwrap_or(0) } else { left };
+    // rounded to nearest, (1 << shift) >> 1 is 0 when division_shift=0
+    let step = ((right - left) * frac + ((1 << shift) >> 1)) >> shift;
+    O::from_bits((left + step).try_into().unwrap_or(table[index]))
+}
+
+fn apply_sign<O: FixedSigned>(value: O, sign: bool) -> O {
+    if sign {
+        value
+    } else {
+        -value
+    }
+}
+
+/*
+1 in the return type, or its maximum value if 1 is not representable
+*/
+fn one<O: FixedSigned>() -> O {
+    O::TRY_ONE.unwrap_or(O::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::sin_table::{fill_table, TableMode, TABLE, TABLE_MODE};
+    use fixed::types::{I1F15, I8F24};
+
+    #[test]
+    fn required_len_covers_every_index() {
+        // a table of exactly required_len is enough for every I4F12 angle and every shift
+        for division_shift in 0..16 {
+            let data = vec![0; SinTable::required_len(division_shift)];
+            let table = SinTable::new(&data, division_shift, I2F14::FRAC_NBITS, MAX_REDUCED_ANGLE);
+            for bits in i16::MIN..=i16::MAX {
+                let angle = I4F12::from_bits(bits);
+                sincos_t(angle, &table);
+                sincos_t_interp(angle, &table);
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "sin table too short")]
+    fn short_table_rejected() {
+        let data = vec![0; SinTable::required_len(5) - 1];
+        SinTable::new(&data, 5, I2F14::FRAC_NBITS, MAX_REDUCED_ANGLE);
+    }
+
+    #[test]
+    #[should_panic(expected = "samples must be I2F14")]
+    fn other_output_rejected() {
+        // an I1F15 table, e.g. from Sin_Table_Create --type I1F15, would double every value
+        let data = vec![0; SinTable::required_len(5)];
+        SinTable::new(&data, 5, I1F15::FRAC_NBITS, MAX_REDUCED_ANGLE);
+    }
+
+    #[test]
+    #[should_panic(expected = "end angle below the largest reduced angle")]
+    fn short_span_rejected() {
+        // a table to PI/2 only misses the samples of the reduced angles past it
+        let data = vec![0; (I4F12::FRAC_PI_2.to_bits() >> 5) as usize + 1];
+        SinTable::new(&data, 5, I2F14::FRAC_NBITS, I4F12::FRAC_PI_2);
+    }
+
+    #[test]
+    #[should_panic(expected = "length does not match its end angle")]
+    fn other_span_rejected() {
+        // a table to PI with the length of a table to MAX_REDUCED_ANGLE
+        let data = vec![0; SinTable::required_len(5)];
+        SinTable::new(&data, 5, I2F14::FRAC_NBITS, I4F12::PI);
+    }
+
+    #[test]
+    fn longer_span_accepted() {
+        let data = vec![0; (I4F12::PI.to_bits() >> 5) as usize + 1];
+        let table = SinTable::new(&data, 5, I2F14::FRAC_NBITS, I4F12::PI);
+        assert_eq!((table.output_frac_nbits(), table.end_angle()), (I2F14::FRAC_NBITS, I4F12::PI));
+        assert_eq!(sin_t(I4F12::FRAC_PI_2, &table), I2F14::ZERO);
+    }
+
+    /*
+    property tests of the shipped table, sin_table::TABLE with its division_shift and mode,
+    over every I4F12 angle inside -2PI to 2PI, with the lookups the mode is made for:
+    left sample (sin_t, cos_t, sincos_t) and interpolated for a left edge table, only one
+    of them for the optimised tables
+    the tolerances follow the table samples so they hold for any division_shift
+    */
+    fn lookups() -> &'static [bool] {
+        match TABLE_MODE {
+            TableMode::LeftEdge => &[false, true],
+            TableMode::NearestMinimax | TableMode::NearestRms => &[false],
+            TableMode::InterpolatedMinimax | TableMode::InterpolatedRms => &[true],
+        }
+    }
+
+    fn shipped(interpolate: bool) -> TableTrig<'static> {
+        TableTrig { table: TABLE, interpolate }
+    }
+
+    fn every_angle() -> impl Iterator<Item = I4F12> {
+        let two_pi = (2 * I4F12::PI).to_bits();
+        (-two_pi + 1..two_pi).map(I4F12::from_bits)
+    }
+
+    fn lsb(steps: i32) -> f64 {
+        steps as f64 * I2F14::DELTA.to_num::<f64>()
+    }
+
+    /*
+    largest error of sin and cos against f64: one table step of angle for the left
+    sample, the chord error h^2/8 interpolated, plus the error of the truncated PI
+    constants in the angle reduction and the rounding of the samples
+    */
+    fn max_error(interpolate: bool) -> f64 {
+        let step = (1 << TABLE.division_shift()) as f64 * I4F12::DELTA.to_num::<f64>();
+        if interpolate { step * step / 8.0 + lsb(16) } else { step + lsb(16) }
+    }
+
+    /*
+    largest change of the result when the reduced angle moves by angle_bits I4F12 steps:
+    the table steps crossed for the left sample, the slope (at most 1) interpolated
+    at the zero crossings the sign changes, so the first sample counts twice
+    */
+    fn max_change(interpolate: bool, angle_bits: i32) -> f64 {
+        if interpolate {
+            lsb(angle_bits << (I2F14::FRAC_NBITS - I4F12::FRAC_NBITS)) + lsb(1)
+        } else {
+            let data = TABLE.data();
+            let step = data.windows(2).map(|pair| (pair[1] - pair[0]).abs()).max().unwrap_or(0).max(2 * data[0].abs());
+            lsb(step as i32 * ((angle_bits >> TABLE.division_shift()) + 1))
+        }
+    }
+
+    fn f(value: I2F14) -> f64 {
+        value.to_num()
+    }
+
+    #[test]
+    fn sin_cos_reference() {
+        for &interpolate in lookups() {
+            let trig = shipped(interpolate);
+            for angle in every_angle() {
+                let angle_f: f64 = angle.to_num();
+                assert!((f(trig.sin(angle)) - angle_f.sin()).abs() <= max_error(interpolate), "sin {} interpolate {}", angle, interpolate);
+                assert!((f(trig.cos(angle)) - angle_f.cos()).abs() <= max_error(interpolate), "cos {} interpolate {}", angle, interpolate);
+            }
+        }
+    }
+
+    #[test]
+    fn pythagorean_identity() {
+        for &interpolate in lookups() {
+            let trig = shipped(interpolate);
+            for angle in every_angle() {
+                let (sin, cos) = trig.sincos(angle);
+                assert_eq!((sin, cos), (trig.sin(angle), trig.cos(angle)), "sincos {} interpolate {}", angle, interpolate);
+                // d(sin^2+cos^2) = 2*(sin*dsin + cos*dcos), |sin|+|cos| <= sqrt(2)
+                let error = (f(sin) * f(sin) + f(cos) * f(cos) - 1.0).abs();
+                assert!(error <= 3.0 * max_error(interpolate), "sin^2+cos^2 {} at {} interpolate {}", error, angle, interpolate);
+            }
+        }
+    }
+
+    #[test]
+    fn sin_odd_cos_even() {
+        // -angle has the same reduced angle of angle, only at the zero crossings the sign
+        // of the first sample can differ, exact for a first sample of 0
+        let tolerance = lsb(2 * TABLE.data()[0].abs() as i32);
+        for &interpolate in lookups() {
+            let trig = shipped(interpolate);
+            for angle in every_angle() {
+                assert!((f(trig.sin(-angle)) + f(trig.sin(angle))).abs() <= tolerance, "sin {} interpolate {}", angle, interpolate);
+                assert!((f(trig.cos(-angle)) - f(trig.cos(angle))).abs() <= tolerance, "cos {} interpolate {}", angle, interpolate);
+            }
+        }
+    }
+
+    #[test]
+    fn cos_is_shifted_sin() {
+        // the truncated PI/2 moves the reduced angles of the two sides by up to 2 steps
+        for &interpolate in lookups() {
+            let trig = shipped(interpolate);
+            for angle in every_angle().filter(|angle| *angle < 2 * I4F12::PI - I4F12::FRAC_PI_2) {
+                let error = (f(trig.cos(angle)) - f(trig.sin(angle + I4F12::FRAC_PI_2))).abs();
+                assert!(error <= max_change(interpolate, 2), "cos - sin(+PI/2) {} at {} interpolate {}", error, angle, interpolate);
+            }
+        }
+    }
+
+    #[test]
+    fn continuous_across_quadrants() {
+        // adjacent angles, including the ones across the PI/2 quadrant boundaries,
+        // differ by no more than adjacent angles inside a quadrant
+        for &interpolate in lookups() {
+            let trig = shipped(interpolate);
+            let angles: Vec<I4F12> = every_angle().collect();
+            for pair in angles.windows(2) {
+                let (sin_jump, cos_jump) = ((f(trig.sin(pair[1])) - f(trig.sin(pair[0]))).abs(), (f(trig.cos(pair[1])) - f(trig.cos(pair[0]))).abs());
+                assert!(sin_jump <= max_change(interpolate, 1), "sin jump {} at {} interpolate {}", sin_jump, pair[1], interpolate);
+                assert!(cos_jump <= max_change(interpolate, 1), "cos jump {} at {} interpolate {}", cos_jump, pair[1], interpolate);
+            }
+            for quadrant in -3..=3 {
+                let boundary = I4F12::FRAC_PI_2 * quadrant;
+                let (sin, cos) = ((quadrant as f64 * core::f64::consts::FRAC_PI_2).sin(), (quadrant as f64 * core::f64::consts::FRAC_PI_2).cos());
+                assert!((f(trig.sin(boundary)) - sin).abs() <= max_error(interpolate), "sin {}*PI/2 interpolate {}", quadrant, interpolate);
+                assert!((f(trig.cos(boundary)) - cos).abs() <= max_error(interpolate), "cos {}*PI/2 interpolate {}", quadrant, interpolate);
+            }
+        }
+    }
+
+    #[test]
+    fn beyond_two_pi() {
+        // at and beyond +-2PI sin is 0 and cos is 1, up to the limits of I4F12
+        let two_pi = (2 * I4F12::PI).to_bits();
+        for &interpolate in lookups() {
+            let trig = shipped(interpolate);
+            for bits in (i16::MIN..=-two_pi).chain(two_pi..=i16::MAX) {
+                let angle = I4F12::from_bits(bits);
+                assert_eq!(trig.sin(angle), I2F14::ZERO, "sin {} interpolate {}", angle, interpolate);
+                assert_eq!(trig.cos(angle), I2F14::ONE, "cos {} interpolate {}", angle, interpolate);
+                assert_eq!(trig.sincos(angle), (I2F14::ZERO, I2F14::ONE), "sincos {} interpolate {}", angle, interpolate);
+            }
+            // the fixed values continue the ones just inside +-2PI
+            for bits in [-two_pi + 1, two_pi - 1] {
+                let angle = I4F12::from_bits(bits);
+                assert!(f(trig.sin(angle)).abs() <= max_error(interpolate), "sin {} interpolate {}", angle, interpolate);
+                assert!(1.0 - f(trig.cos(angle)) <= max_error(interpolate), "cos {} interpolate {}", angle, interpolate);
+            }
+        }
+    }
+
+    /*
+    generic functions with other types than I4F12 and I2F14: a left edge table of the
+    given output type with one sample every 2^-7 rad, covering the largest reduced angle
+    of the angle type, and the error bound of max_error for that table: the truncated
+    PI constants of the angle type and the rounding of the samples
+    */
+    fn fixed_table<A: FixedSigned>(output_frac_nbits: u32) -> (Vec<i16>, u16) {
+        let division_shift = (A::FRAC_NBITS - 7) as u16;
+        let (frac_pi_2, _, three_frac_pi_2, two_pi) = quadrant_limits::<A>();
+        let max_reduced: i64 = (two_pi - three_frac_pi_2).max(frac_pi_2).to_bits().try_into().unwrap_or(0);
+        let mut data = vec![0; (max_reduced >> division_shift) as usize + 1];
+        fill_table(&mut data, A::FRAC_NBITS, output_frac_nbits, division_shift, TableMode::LeftEdge);
+        (data, division_shift)
+    }
+
+    fn fixed_max_error(interpolate: bool, angle_lsb: f64, output_lsb: f64) -> f64 {
+        let step = 1.0 / 128.0;
+        let rounding = 4.0 * angle_lsb + 4.0 * output_lsb;
+        if interpolate { step * step / 8.0 + rounding } else { step + rounding }
+    }
+
+    fn check_fixed<A: FixedSigned, O: FixedSigned>(angles: impl Iterator<Item = A>, table: &[O::Bits], division_shift: u16) {
+        let (angle_lsb, output_lsb) = (A::DELTA.to_num::<f64>(), O::DELTA.to_num::<f64>());
+        for angle in angles {
+            let angle_f: f64 = angle.to_num();
+            let results = [
+                (false, sin_fixed::<A, O>(angle, table, division_shift), cos_fixed::<A, O>(angle, table, division_shift), sincos_fixed::<A, O>(angle, table, division_shift)),
+                (true, sin_fixed_interp::<A, O>(angle, table, division_shift), cos_fixed_interp::<A, O>(angle, table, division_shift), sincos_fixed_interp::<A, O>(angle, table, division_shift)),
+            ];
+            for (interpolate, sin, cos, sincos) in results {
+                assert_eq!(sincos, (sin, cos), "sincos {} interpolate {}", angle, interpolate);
+                let tolerance = fixed_max_error(interpolate, angle_lsb, output_lsb);
+                assert!((sin.to_num::<f64>() - angle_f.sin()).abs() <= tolerance, "sin {} interpolate {}: {}", angle, interpolate, sin);
+                assert!((cos.to_num::<f64>() - angle_f.cos()).abs() <= tolerance, "cos {} interpolate {}: {}", angle, interpolate, cos);
+            }
+        }
+    }
+
+    #[test]
+    fn fixed_wider_angle() {
+        // I8F24 angle, I2F14 output, every 2^12 angle steps over -2PI to 2PI
+        let (data, division_shift) = fixed_table::<I8F24>(I2F14::FRAC_NBITS);
+        let two_pi = (2 * I8F24::PI).to_bits();
+        check_fixed::<I8F24, I2F14>((-two_pi + 1..two_pi).step_by(1 << 12).map(I8F24::from_bits), &data, division_shift);
+        // the quadrant boundaries and the angles next to them
+        let boundaries = (-4..=4).flat_map(|quadrant| {
+            let bits = (I8F24::FRAC_PI_2 * quadrant).to_bits();
+            [bits - 1, bits, bits + 1]
+        });
+        check_fixed::<I8F24, I2F14>(boundaries.filter(|bits| bits.abs() < two_pi).map(I8F24::from_bits), &data, division_shift);
+        // beyond +-2PI, which I8F24 can hold up to +-128
+        for angle in [2 * I8F24::PI, -2 * I8F24::PI, I8F24::from_num(10), I8F24::MIN, I8F24::MAX] {
+            assert_eq!(sincos_fixed::<I8F24, I2F14>(angle, &data, division_shift), (I2F14::ZERO, I2F14::ONE), "{}", angle);
+            assert_eq!(sincos_fixed_interp::<I8F24, I2F14>(angle, &data, division_shift), (I2F14::ZERO, I2F14::ONE), "{}", angle);
+        }
+    }
+
+    #[test]
+    fn fixed_output_without_one() {
+        // I1F15 output cannot hold 1: the samples saturate at I1F15::MAX and so do the
+        // results at +-PI/2, 0 and beyond +-2PI, without wrapping to negative values
+        let (data, division_shift) = fixed_table::<I4F12>(I1F15::FRAC_NBITS);
+        assert_eq!(data.iter().max(), Some(&i16::MAX));
+        check_fixed::<I4F12, I1F15>(every_angle(), &data, division_shift);
+        for interpolate in [false, true] {
+            let (sin, cos) = if interpolate {
+                (sin_fixed_interp::<I4F12, I1F15> as fn(I4F12, &[i16], u16) -> I1F15, cos_fixed_interp::<I4F12, I1F15> as fn(I4F12, &[i16], u16) -> I1F15)
+            } else {
+                (sin_fixed::<I4F12, I1F15> as fn(I4F12, &[i16], u16) -> I1F15, cos_fixed::<I4F12, I1F15> as fn(I4F12, &[i16], u16) -> I1F15)
+            };
+            assert_eq!(sin(I4F12::FRAC_PI_2, &data, division_shift), I1F15::MAX, "interpolate {}", interpolate);
+            assert_eq!(sin(-I4F12::FRAC_PI_2, &data, division_shift), -I1F15::MAX, "interpolate {}", interpolate);
+            assert_eq!(cos(I4F12::ZERO, &data, division_shift), I1F15::MAX, "interpolate {}", interpolate);
+            assert_eq!(cos(I4F12::PI, &data, division_shift), -I1F15::MAX, "interpolate {}", interpolate);
+            for bits in (i16::MIN..=i16::MAX).step_by(7) {
+                let angle = I4F12::from_bits(bits);
+                assert!(sin(angle, &data, division_shift) >= -I1F15::MAX, "sin {} interpolate {}", angle, interpolate);
+                assert!(cos(angle, &data, division_shift) >= -I1F15::MAX, "cos {} interpolate {}", angle, interpolate);
+            }
+        }
+        let beyond = 2 * I4F12::PI;
+        assert_eq!(sincos_fixed::<I4F12, I1F15>(beyond, &data, division_shift), (I1F15::ZERO, I1F15::MAX));
+        assert_eq!(sincos_fixed_interp::<I4F12, I1F15>(-beyond, &data, division_shift), (I1F15::ZERO, I1F15::MAX));
+    }
+}